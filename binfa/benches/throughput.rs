@@ -0,0 +1,159 @@
+/*
+比较工作窃取线程池和原来共享 Mutex<mpsc::Receiver> 的线程池执行大量小任务的吞吐量
+运行：cargo bench --bench throughput
+*/
+use binfa::ThreadPool;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const THREADS: usize = 4;
+const TINY_JOBS: usize = 200_000;
+const PARENTS: usize = 100;
+const CHILDREN: usize = 2_000;
+const ROUNDS: usize = 3;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// 原来的实现：所有工作线程抢同一个 Mutex<mpsc::Receiver>
+struct BaselinePool {
+    sender: Mutex<Option<mpsc::Sender<Job>>>,
+    workers: Mutex<Vec<thread::JoinHandle<()>>>,
+}
+
+impl BaselinePool {
+    fn new(size: usize) -> BaselinePool {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..size)
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                thread::spawn(move || {
+                    loop {
+                        let message = receiver.lock().unwrap().recv();
+                        match message {
+                            Ok(job) => job(),
+                            Err(_) => break,
+                        }
+                    }
+                })
+            })
+            .collect();
+        BaselinePool {
+            sender: Mutex::new(Some(sender)),
+            workers: Mutex::new(workers),
+        }
+    }
+
+    fn execute<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        if let Some(sender) = self.sender.lock().unwrap().as_ref() {
+            sender.send(Box::new(f)).unwrap();
+        }
+    }
+
+    fn shutdown(&self) {
+        drop(self.sender.lock().unwrap().take());
+        for worker in self.workers.lock().unwrap().drain(..) {
+            worker.join().unwrap();
+        }
+    }
+}
+
+/// 两种线程池共用的提交接口
+trait Pool: Send + Sync + 'static {
+    fn spawn(&self, job: Job);
+}
+
+impl Pool for BaselinePool {
+    fn spawn(&self, job: Job) {
+        self.execute(job);
+    }
+}
+
+impl Pool for ThreadPool {
+    fn spawn(&self, job: Job) {
+        self.execute(job);
+    }
+}
+
+/// 等到计数器达到 `expected`
+fn wait_for(counter: &AtomicUsize, expected: usize) {
+    while counter.load(Ordering::Acquire) < expected {
+        thread::yield_now();
+    }
+}
+
+/// 从外部线程提交大量小任务
+fn tiny_jobs<P: Pool>(pool: &Arc<P>) -> Duration {
+    let done = Arc::new(AtomicUsize::new(0));
+    let start = Instant::now();
+    for _ in 0..TINY_JOBS {
+        let done = Arc::clone(&done);
+        pool.spawn(Box::new(move || {
+            done.fetch_add(1, Ordering::Release);
+        }));
+    }
+    wait_for(&done, TINY_JOBS);
+    start.elapsed()
+}
+
+/// 任务里再提交小任务
+fn nested_jobs<P: Pool>(pool: &Arc<P>) -> Duration {
+    let done = Arc::new(AtomicUsize::new(0));
+    let start = Instant::now();
+    for _ in 0..PARENTS {
+        let done = Arc::clone(&done);
+        let inner = Arc::clone(pool);
+        pool.spawn(Box::new(move || {
+            for _ in 0..CHILDREN {
+                let done = Arc::clone(&done);
+                inner.spawn(Box::new(move || {
+                    done.fetch_add(1, Ordering::Release);
+                }));
+            }
+        }));
+    }
+    wait_for(&done, PARENTS * CHILDREN);
+    start.elapsed()
+}
+
+/// 取几轮里最快的一次，换算成每秒任务数
+fn best<P: Pool>(pool: &Arc<P>, jobs: usize, run: fn(&Arc<P>) -> Duration) -> f64 {
+    let fastest = (0..ROUNDS).map(|_| run(pool)).min().unwrap();
+    jobs as f64 / fastest.as_secs_f64()
+}
+
+fn report(name: &str, baseline: f64, stealing: f64) {
+    println!("{}：", name);
+    println!("  Mutex<mpsc::Receiver>：{:>12.0} 任务/秒", baseline);
+    println!("  工作窃取：             {:>12.0} 任务/秒", stealing);
+    println!("  提升：                 {:>12.2} 倍", stealing / baseline);
+}
+
+fn main() {
+    println!("{} 个线程，每项取 {} 轮中最快的一次\n", THREADS, ROUNDS);
+
+    let baseline = Arc::new(BaselinePool::new(THREADS));
+    let baseline_tiny = best(&baseline, TINY_JOBS, tiny_jobs);
+    let baseline_nested = best(&baseline, PARENTS * CHILDREN, nested_jobs);
+    baseline.shutdown();
+
+    let stealing = Arc::new(ThreadPool::new(THREADS));
+    let stealing_tiny = best(&stealing, TINY_JOBS, tiny_jobs);
+    let stealing_nested = best(&stealing, PARENTS * CHILDREN, nested_jobs);
+
+    report(
+        &format!("外部提交 {} 个小任务", TINY_JOBS),
+        baseline_tiny,
+        stealing_tiny,
+    );
+    report(
+        &format!("{} 个任务各自再提交 {} 个小任务", PARENTS, CHILDREN),
+        baseline_nested,
+        stealing_nested,
+    );
+}
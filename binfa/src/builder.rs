@@ -0,0 +1,190 @@
+/*
+在这个文件里面实现线程池的构建器
+ThreadPoolBuilder：核心/最大线程数与空闲线程的存活时间、线程名前缀、栈大小、任务队列容量与拒绝策略、
+优先级提升的间隔、每个工作线程启动/退出时的回调以及任务 panic 时的处理函数，build 时统一校验
+*/
+use crate::queue::{JobQueue, RejectionPolicy};
+use crate::{MAX_THREADS, PoolCreationError, PoolSizing, ThreadPool};
+use std::any::Any;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// 默认的空闲线程存活时间
+const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(60);
+
+/// 默认每排队多久提升一级优先级
+const DEFAULT_PRIORITY_AGING: Duration = Duration::from_millis(500);
+
+/// 工作线程启动或退出时调用，参数是 worker 的编号
+pub(crate) type WorkerHook = Arc<dyn Fn(usize) + Send + Sync>;
+
+/// 任务 panic 时调用，参数是 panic 的负载
+pub(crate) type PanicHandler = Arc<dyn Fn(Box<dyn Any + Send>) + Send + Sync>;
+
+/// 启动工作线程时用到的配置
+#[derive(Default)]
+pub(crate) struct WorkerConfig {
+    pub(crate) thread_name: Option<String>,
+    pub(crate) stack_size: Option<usize>,
+    pub(crate) on_thread_start: Option<WorkerHook>,
+    pub(crate) on_thread_exit: Option<WorkerHook>,
+    pub(crate) panic_handler: Option<PanicHandler>,
+}
+
+/// 线程池构建器
+///
+/// ```
+/// use binfa::{RejectionPolicy, ThreadPoolBuilder};
+///
+/// let pool = ThreadPoolBuilder::new()
+///     .num_threads(2)
+///     .thread_name("下载")
+///     .queue_capacity(16)
+///     .rejection_policy(RejectionPolicy::CallerRuns)
+///     .build()
+///     .expect("创建线程池失败");
+/// let name = pool.execute_with_result(|| std::thread::current().name().map(String::from));
+/// assert!(name.join().unwrap().unwrap().starts_with("下载-"));
+/// ```
+#[derive(Default)]
+pub struct ThreadPoolBuilder {
+    num_threads: Option<usize>,
+    max_threads: Option<usize>,
+    keep_alive: Option<Duration>,
+    priority_aging: Option<Duration>,
+    queue_capacity: Option<usize>,
+    rejection_policy: RejectionPolicy,
+    config: WorkerConfig,
+}
+
+impl ThreadPoolBuilder {
+    pub fn new() -> ThreadPoolBuilder {
+        ThreadPoolBuilder::default()
+    }
+
+    /// 核心线程数，默认等于可用的 CPU 核数；核心线程空闲时也不会退出
+    pub fn num_threads(mut self, num_threads: usize) -> ThreadPoolBuilder {
+        self.num_threads = Some(num_threads);
+        self
+    }
+
+    /// 最大线程数，默认等于核心线程数（固定大小）；
+    /// 排队的任务比空闲线程多时，在这个数量以内临时增加线程
+    pub fn max_threads(mut self, max_threads: usize) -> ThreadPoolBuilder {
+        self.max_threads = Some(max_threads);
+        self
+    }
+
+    /// 超过核心线程数的线程空闲多久后退出，默认 60 秒
+    pub fn keep_alive(mut self, keep_alive: Duration) -> ThreadPoolBuilder {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+
+    /// 任务排队每满这么久提升一级优先级，默认 500 毫秒；
+    /// 低优先级的任务最多等两倍这么久就和高优先级的任务按先来后到执行，为 0 时不区分优先级
+    pub fn priority_aging(mut self, aging: Duration) -> ThreadPoolBuilder {
+        self.priority_aging = Some(aging);
+        self
+    }
+
+    /// 线程名前缀，工作线程命名为 `前缀-编号`
+    pub fn thread_name(mut self, prefix: impl Into<String>) -> ThreadPoolBuilder {
+        self.config.thread_name = Some(prefix.into());
+        self
+    }
+
+    /// 工作线程的栈大小（字节），默认使用标准库的设置
+    pub fn stack_size(mut self, bytes: usize) -> ThreadPoolBuilder {
+        self.config.stack_size = Some(bytes);
+        self
+    }
+
+    /// 任务队列最多容纳的任务数，默认不限
+    pub fn queue_capacity(mut self, capacity: usize) -> ThreadPoolBuilder {
+        self.queue_capacity = Some(capacity);
+        self
+    }
+
+    /// 队列满时如何处理新任务，默认阻塞提交者
+    pub fn rejection_policy(mut self, policy: RejectionPolicy) -> ThreadPoolBuilder {
+        self.rejection_policy = policy;
+        self
+    }
+
+    /// 每个工作线程启动时（包括 panic 后重新启动）在该线程上调用
+    pub fn on_thread_start<F>(mut self, hook: F) -> ThreadPoolBuilder
+    where
+        F: Fn(usize) + Send + Sync + 'static,
+    {
+        self.config.on_thread_start = Some(Arc::new(hook));
+        self
+    }
+
+    /// 每个工作线程正常退出前在该线程上调用
+    pub fn on_thread_exit<F>(mut self, hook: F) -> ThreadPoolBuilder
+    where
+        F: Fn(usize) + Send + Sync + 'static,
+    {
+        self.config.on_thread_exit = Some(Arc::new(hook));
+        self
+    }
+
+    /// 任务 panic 时在工作线程上调用；
+    /// execute_with_result 的任务负载已经交给了 JobHandle，这里收到的是 panic 消息（`String`）
+    pub fn panic_handler<F>(mut self, handler: F) -> ThreadPoolBuilder
+    where
+        F: Fn(Box<dyn Any + Send>) + Send + Sync + 'static,
+    {
+        self.config.panic_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// 校验所有选项并启动线程池
+    pub fn build(self) -> Result<ThreadPool, PoolCreationError> {
+        let num_threads = self.num_threads.unwrap_or_else(|| {
+            thread::available_parallelism()
+                .map_or(1, NonZeroUsize::get)
+                .min(MAX_THREADS)
+        });
+        if num_threads == 0 {
+            return Err(PoolCreationError::ZeroSize);
+        }
+        if num_threads > MAX_THREADS {
+            return Err(PoolCreationError::TooLarge(num_threads));
+        }
+
+        let max_threads = self.max_threads.unwrap_or(num_threads);
+        if max_threads < num_threads {
+            return Err(PoolCreationError::MaxBelowCore {
+                core: num_threads,
+                max: max_threads,
+            });
+        }
+        if max_threads > MAX_THREADS {
+            return Err(PoolCreationError::TooLarge(max_threads));
+        }
+
+        if self.queue_capacity == Some(0) {
+            return Err(PoolCreationError::ZeroQueueCapacity);
+        }
+
+        if self.config.stack_size == Some(0) {
+            return Err(PoolCreationError::ZeroStackSize);
+        }
+
+        if let Some(name) = &self.config.thread_name
+            && (name.is_empty() || name.contains('\0'))
+        {
+            return Err(PoolCreationError::InvalidThreadName(name.clone()));
+        }
+
+        let aging = self.priority_aging.unwrap_or(DEFAULT_PRIORITY_AGING);
+        let queue = JobQueue::new(self.queue_capacity, self.rejection_policy, aging);
+        let keep_alive = self.keep_alive.unwrap_or(DEFAULT_KEEP_ALIVE);
+        let sizing = PoolSizing::new(num_threads, max_threads, keep_alive);
+        ThreadPool::start(sizing, queue, self.config)
+    }
+}
@@ -0,0 +1,239 @@
+/*
+在这个文件里面实现带返回值任务的句柄
+JobHandle：execute_with_result 返回的句柄，可以等待结果、限时等待、查询是否完成，
+以及取消还在排队的任务；任务的结果或失败原因统一用 JobError 表示
+*/
+use std::any::Any;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// 任务执行时发生了 panic，保存 panic 的负载
+#[derive(Debug)]
+pub struct JobPanicked {
+    payload: Box<dyn Any + Send>,
+}
+
+impl JobPanicked {
+    /// panic 的消息，负载不是字符串时返回 "未知的 panic"
+    pub fn message(&self) -> &str {
+        panic_message(self.payload.as_ref())
+    }
+
+    /// 取出原始负载，可以用 `std::panic::resume_unwind` 继续传播
+    pub fn into_payload(self) -> Box<dyn Any + Send> {
+        self.payload
+    }
+}
+
+impl fmt::Display for JobPanicked {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "任务执行时 panic：{}", self.message())
+    }
+}
+
+impl std::error::Error for JobPanicked {}
+
+/// `panic!` 的负载通常是 `&str` 或 `String`
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "未知的 panic"
+    }
+}
+
+/// 没能拿到任务结果的原因
+#[derive(Debug)]
+pub enum JobError {
+    /// 任务执行时 panic
+    Panicked(JobPanicked),
+    /// 任务在排队时被取消
+    Cancelled,
+    /// 任务还没执行就被线程池丢弃了
+    Dropped,
+    /// 任务还没有完成（try_join）
+    NotFinished,
+    /// 等待超时，任务还没有完成（join_timeout）
+    Timeout,
+    /// 结果已经被取走了
+    AlreadyJoined,
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JobError::Panicked(panicked) => write!(f, "{}", panicked),
+            JobError::Cancelled => write!(f, "任务已取消"),
+            JobError::Dropped => write!(f, "任务没有执行就被线程池丢弃"),
+            JobError::NotFinished => write!(f, "任务还没有完成"),
+            JobError::Timeout => write!(f, "等待任务结果超时"),
+            JobError::AlreadyJoined => write!(f, "任务结果已经被取走"),
+        }
+    }
+}
+
+impl std::error::Error for JobError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JobError::Panicked(panicked) => Some(panicked),
+            _ => None,
+        }
+    }
+}
+
+/// 任务所处的阶段
+enum JobStatus<T> {
+    Queued,
+    Running,
+    Finished(Result<T, JobError>),
+    Joined,
+}
+
+/// 句柄和线程池中的任务共享的状态
+struct JobState<T> {
+    status: Mutex<JobStatus<T>>,
+    finished: Condvar,
+}
+
+impl<T> JobState<T> {
+    fn lock(&self) -> MutexGuard<'_, JobStatus<T>> {
+        self.status.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 任务出队时调用，返回 false 表示任务已经被取消
+    fn start(&self) -> bool {
+        let mut status = self.lock();
+        if !matches!(*status, JobStatus::Queued) {
+            return false;
+        }
+        *status = JobStatus::Running;
+        true
+    }
+
+    fn finish(&self, result: Result<T, JobError>) {
+        *self.lock() = JobStatus::Finished(result);
+        self.finished.notify_all();
+    }
+}
+
+/// 任务闭包持有的那一份状态；任务没有执行完就被丢弃时通知句柄
+struct JobGuard<T>(Arc<JobState<T>>);
+
+impl<T> Drop for JobGuard<T> {
+    fn drop(&mut self) {
+        let mut status = self.0.lock();
+        if matches!(*status, JobStatus::Queued | JobStatus::Running) {
+            *status = JobStatus::Finished(Err(JobError::Dropped));
+            self.0.finished.notify_all();
+        }
+    }
+}
+
+/// 把任务包装成交给线程池执行的闭包，并返回对应的句柄
+pub(crate) fn wrap<F, T>(f: F) -> (impl FnOnce() + Send + 'static, JobHandle<T>)
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let state = Arc::new(JobState {
+        status: Mutex::new(JobStatus::Queued),
+        finished: Condvar::new(),
+    });
+    let guard = JobGuard(Arc::clone(&state));
+
+    let job = move || {
+        let guard = guard;
+        if !guard.0.start() {
+            return;
+        }
+        match panic::catch_unwind(AssertUnwindSafe(f)) {
+            Ok(result) => guard.0.finish(Ok(result)),
+            Err(payload) => {
+                let message = panic_message(payload.as_ref()).to_string();
+                guard
+                    .0
+                    .finish(Err(JobError::Panicked(JobPanicked { payload })));
+                // 负载已经交给句柄，继续传播消息让 worker 计入 panicked_count
+                panic::resume_unwind(Box::new(message));
+            }
+        }
+    };
+
+    (job, JobHandle { state })
+}
+
+/// 带返回值任务的句柄
+pub struct JobHandle<T> {
+    state: Arc<JobState<T>>,
+}
+
+impl<T> JobHandle<T> {
+    /// 阻塞等待任务结束并取出结果
+    pub fn join(self) -> Result<T, JobError> {
+        let status = self
+            .state
+            .finished
+            .wait_while(self.state.lock(), |status| !is_finished(status))
+            .unwrap_or_else(PoisonError::into_inner);
+        take_result(status)
+    }
+
+    /// 不阻塞；任务还没有完成时返回 `JobError::NotFinished`
+    pub fn try_join(&self) -> Result<T, JobError> {
+        let status = self.state.lock();
+        if !is_finished(&status) {
+            return Err(JobError::NotFinished);
+        }
+        take_result(status)
+    }
+
+    /// 最多等待 `timeout`；超时返回 `JobError::Timeout`，之后还可以继续等待
+    pub fn join_timeout(&self, timeout: Duration) -> Result<T, JobError> {
+        let deadline = Instant::now() + timeout;
+        let mut status = self.state.lock();
+        while !is_finished(&status) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(JobError::Timeout);
+            }
+            status = self
+                .state
+                .finished
+                .wait_timeout(status, remaining)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        take_result(status)
+    }
+
+    /// 任务是否已经结束（包括 panic、被取消和被丢弃）
+    pub fn is_finished(&self) -> bool {
+        is_finished(&self.state.lock())
+    }
+
+    /// 取消还在排队的任务，返回是否取消成功；已经开始执行的任务无法取消
+    pub fn cancel(&self) -> bool {
+        let mut status = self.state.lock();
+        if !matches!(*status, JobStatus::Queued) {
+            return false;
+        }
+        *status = JobStatus::Finished(Err(JobError::Cancelled));
+        self.state.finished.notify_all();
+        true
+    }
+}
+
+fn is_finished<T>(status: &JobStatus<T>) -> bool {
+    matches!(status, JobStatus::Finished(_) | JobStatus::Joined)
+}
+
+fn take_result<T>(mut status: MutexGuard<'_, JobStatus<T>>) -> Result<T, JobError> {
+    match std::mem::replace(&mut *status, JobStatus::Joined) {
+        JobStatus::Finished(result) => result,
+        _ => Err(JobError::AlreadyJoined),
+    }
+}
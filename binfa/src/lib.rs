@@ -0,0 +1,725 @@
+//! 线程池
+//!
+//! [`ThreadPool`] 启动若干工作线程执行任务。从外部提交的任务在全局队列里排队，按提交顺序取出执行；
+//! 每个工作线程还有自己的任务队列，执行任务时再提交的任务放在这里，
+//! 自己的队列空了就去别的线程的队列里偷一半，减少所有线程争抢同一把锁。
+//! 其他程序（比如 my_web）可以直接依赖本 crate，而不必再复制一份线程池代码。
+//!
+//! 任务 panic 不会拖垮工作线程：panic 被捕获后记入 [`ThreadPool::panicked_count`]，
+//! [`ThreadPool::execute_with_result`] 返回的 [`JobHandle`] 会得到 [`JobError::Panicked`]。
+//!
+//! [`ThreadPool::scope`] 里提交的任务可以借用调用者栈上的数据，不必为了 `'static` 到处克隆 `Arc`。
+//! 在它之上有 [`ThreadPool::map`]、[`ThreadPool::for_each`]、[`ThreadPool::map_reduce`]
+//! 和 [`ThreadPool::par_sort`]，把数据分块交给工作线程，结果保持输入顺序。
+//!
+//! 线程数可以在核心线程数和最大线程数之间伸缩：没有空闲线程时临时增加线程，
+//! 多出来的线程空闲超过 keep-alive 时间后退出；[`ThreadPool::resize`] 可以在运行时调整核心线程数。
+//!
+//! [`ThreadPool::execute_with_priority`] 按 [`Priority`] 分高、普通、低三级排队，
+//! 排队太久的任务逐级提升优先级；[`ThreadPool::queued_count_by_priority`] 查看每一级排队的任务数。
+//!
+//! 任务队列默认不限容量；[`ThreadPool::bounded`] 创建有界队列的线程池，
+//! 队列满时按 [`RejectionPolicy`] 处理新任务。线程名、栈大小、回调等更多选项见 [`ThreadPoolBuilder`]。
+//!
+//! ```
+//! use binfa::{JobError, ThreadPool};
+//!
+//! let pool = ThreadPool::build(2).expect("创建线程池失败");
+//! let handle = pool.execute_with_result(|| 6 * 7);
+//! assert_eq!(handle.join().unwrap(), 42);
+//!
+//! let handle = pool.execute_with_result(|| -> i32 { panic!("出错了") });
+//! match handle.join() {
+//!     Err(JobError::Panicked(panicked)) => assert_eq!(panicked.message(), "出错了"),
+//!     _ => unreachable!(),
+//! }
+//! ```
+mod builder;
+mod job;
+mod parallel;
+mod queue;
+mod scope;
+mod steal;
+
+pub use builder::ThreadPoolBuilder;
+pub use job::{JobError, JobHandle, JobPanicked};
+pub use queue::{Priority, RejectionPolicy, SubmitError};
+pub use scope::Scope;
+
+use builder::WorkerConfig;
+use queue::{Job, JobQueue, Popped, Pushed};
+use std::fmt;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+use steal::{LocalQueue, LocalQueues};
+
+/// 线程池最多可以有多少个工作线程
+pub const MAX_THREADS: usize = 1000;
+
+/// 工作线程找不到任务时，睡下之前再找几轮
+const SPIN_ROUNDS: usize = 16;
+
+/// 线程池创建错误
+#[derive(Debug)]
+pub enum PoolCreationError {
+    ZeroSize,
+    TooLarge(usize),
+    ZeroQueueCapacity,
+    ZeroStackSize,
+    /// 线程名前缀为空或者包含 `\0`
+    InvalidThreadName(String),
+    /// 最大线程数小于核心线程数
+    MaxBelowCore {
+        core: usize,
+        max: usize,
+    },
+    /// 操作系统无法创建线程（比如栈太大）
+    Spawn(io::Error),
+}
+
+impl fmt::Display for PoolCreationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PoolCreationError::ZeroSize => write!(f, "线程池大小不能为0"),
+            PoolCreationError::TooLarge(size) => {
+                write!(f, "线程池大小 {} 超过最大限制 {}", size, MAX_THREADS)
+            }
+            PoolCreationError::ZeroQueueCapacity => write!(f, "任务队列容量不能为0"),
+            PoolCreationError::ZeroStackSize => write!(f, "线程栈大小不能为0"),
+            PoolCreationError::InvalidThreadName(name) => {
+                write!(f, "线程名前缀 {:?} 不能为空或包含 \\0", name)
+            }
+            PoolCreationError::MaxBelowCore { core, max } => {
+                write!(f, "最大线程数 {} 小于核心线程数 {}", max, core)
+            }
+            PoolCreationError::Spawn(e) => write!(f, "创建工作线程失败: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PoolCreationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PoolCreationError::Spawn(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// 线程池
+pub struct ThreadPool {
+    /// 工作线程共享的状态：任务队列、工作线程和各种计数
+    context: WorkerContext,
+
+    /// 总提交任务计数
+    submitted_count: AtomicUsize,
+
+    /// 没有执行就被丢弃的任务计数
+    dropped_count: AtomicUsize,
+}
+
+impl ThreadPool {
+    /// 创建线程池（panic 版本，用于简单场景）
+    pub fn new(size: usize) -> ThreadPool {
+        ThreadPool::build(size).expect("创建线程池失败")
+    }
+
+    /// 创建线程池（Result 版本，用于需要错误处理的场景）
+    pub fn build(size: usize) -> Result<ThreadPool, PoolCreationError> {
+        ThreadPoolBuilder::new().num_threads(size).build()
+    }
+
+    /// 创建任务队列最多容纳 `capacity` 个任务的线程池，队列满时按 `policy` 处理新任务
+    pub fn bounded(
+        size: usize,
+        capacity: usize,
+        policy: RejectionPolicy,
+    ) -> Result<ThreadPool, PoolCreationError> {
+        ThreadPoolBuilder::new()
+            .num_threads(size)
+            .queue_capacity(capacity)
+            .rejection_policy(policy)
+            .build()
+    }
+
+    /// 用构建器配置线程池
+    pub fn builder() -> ThreadPoolBuilder {
+        ThreadPoolBuilder::new()
+    }
+
+    /// 按构建器校验过的配置启动核心线程
+    fn start(
+        sizing: PoolSizing,
+        queue: JobQueue,
+        config: WorkerConfig,
+    ) -> Result<ThreadPool, PoolCreationError> {
+        let core = sizing.core.load(Ordering::SeqCst);
+        let context = WorkerContext {
+            queue: Arc::new(queue),
+            workers: Arc::new(Mutex::new(Vec::with_capacity(core))),
+            locals: Arc::new(LocalQueues::default()),
+            sizing: Arc::new(sizing),
+            live_count: Arc::new(AtomicUsize::new(0)),
+            active_count: Arc::new(AtomicUsize::new(0)),
+            completed_count: Arc::new(AtomicUsize::new(0)),
+            panicked_count: Arc::new(AtomicUsize::new(0)),
+            config: Arc::new(config),
+        };
+
+        for _ in 0..core {
+            context.live_count.fetch_add(1, Ordering::SeqCst);
+            if let Err(e) = context.spawn_worker() {
+                // 已经启动的线程要先退出
+                context.queue.close();
+                context.join_workers();
+                return Err(PoolCreationError::Spawn(e));
+            }
+        }
+
+        Ok(ThreadPool {
+            context,
+            submitted_count: AtomicUsize::new(0),
+            dropped_count: AtomicUsize::new(0),
+        })
+    }
+
+    /// 执行任务（panic 版本）
+    /// 线程池已关闭，或者 Fail 策略下队列已满时 panic
+    pub fn execute<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.submit(f).expect("提交任务失败");
+    }
+
+    /// 提交任务（Result 版本），队列满时按拒绝策略处理
+    pub fn submit<F>(&self, f: F) -> Result<(), SubmitError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.push(Box::new(f), Priority::Normal, true)
+    }
+
+    /// 提交任务但从不等待队列空位：Block 策略下队列满时直接返回 `SubmitError::QueueFull`，
+    /// 其他策略与 [`ThreadPool::submit`] 相同
+    pub fn try_execute<F>(&self, f: F) -> Result<(), SubmitError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.push(Box::new(f), Priority::Normal, false)
+    }
+
+    /// 按优先级执行任务（panic 版本），先执行优先级高的任务；
+    /// 排队太久的任务会逐级提升优先级（见 [`ThreadPoolBuilder::priority_aging`]），不会一直轮不到
+    pub fn execute_with_priority<F>(&self, priority: Priority, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.push(Box::new(f), priority, true)
+            .expect("提交任务失败");
+    }
+
+    fn push(&self, job: Job, priority: Priority, wait: bool) -> Result<(), SubmitError> {
+        // 先计数再入队，避免任务完成时已完成数超过已提交数
+        self.submitted_count.fetch_add(1, Ordering::SeqCst);
+
+        // 工作线程执行任务时提交的普通任务放进它自己的队列，其他线程空闲时会来偷；
+        // 高、低优先级的任务都进全局队列，按优先级取
+        if priority == Priority::Normal
+            && self.context.queue.is_unbounded()
+            && let Some((_, local)) = steal::current(&self.context.locals)
+        {
+            if self.context.queue.is_closed() {
+                self.submitted_count.fetch_sub(1, Ordering::SeqCst);
+                return Err(SubmitError::ShutDown);
+            }
+            local.push(job);
+            self.context.queue.signal();
+            return Ok(());
+        }
+
+        match self.context.queue.push(job, priority, wait) {
+            Ok(Pushed::Queued) => {}
+            Ok(Pushed::Evicted(oldest)) => {
+                self.dropped_count.fetch_add(1, Ordering::SeqCst);
+                drop(oldest);
+            }
+            Ok(Pushed::RunHere(job)) => {
+                // 由提交者执行的任务不经过线程池，不计入线程池的计数
+                self.submitted_count.fetch_sub(1, Ordering::SeqCst);
+                job();
+                return Ok(());
+            }
+            Err(e) => {
+                self.submitted_count.fetch_sub(1, Ordering::SeqCst);
+                return Err(e);
+            }
+        }
+
+        // 排队的任务比空闲线程多，在最大线程数以内临时加一个线程
+        self.context.grow();
+
+        Ok(())
+    }
+
+    /// 执行任务并返回句柄，通过句柄等待结果或取消还在排队的任务
+    pub fn execute_with_result<F, T>(&self, f: F) -> JobHandle<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (job, handle) = job::wrap(f);
+        self.execute(job);
+        handle
+    }
+
+    /// 调整核心线程数（最大线程数小于 `size` 时一起调大）
+    /// 线程不够时立即启动新线程；线程多了时空闲的线程立即退出，忙碌的线程做完手上的任务后退出
+    pub fn resize(&self, size: usize) -> Result<(), PoolCreationError> {
+        if size == 0 {
+            return Err(PoolCreationError::ZeroSize);
+        }
+        if size > MAX_THREADS {
+            return Err(PoolCreationError::TooLarge(size));
+        }
+        if self.context.queue.is_closed() {
+            return Ok(());
+        }
+
+        let sizing = &self.context.sizing;
+        sizing.core.store(size, Ordering::SeqCst);
+        sizing.max.fetch_max(size, Ordering::SeqCst);
+
+        let live = self.context.live_count.load(Ordering::SeqCst);
+        let remaining = live.saturating_sub(sizing.retiring.load(Ordering::SeqCst));
+        if remaining > size {
+            sizing
+                .retiring
+                .fetch_add(remaining - size, Ordering::SeqCst);
+            self.context.queue.wake_all();
+            return Ok(());
+        }
+
+        // 先撤销还没执行的退出请求，不够再启动新线程
+        let missing = size - remaining;
+        let revoked = sizing
+            .retiring
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |retiring| {
+                Some(retiring.saturating_sub(missing))
+            })
+            .map_or(0, |retiring| retiring.min(missing));
+        for _ in revoked..missing {
+            self.context.live_count.fetch_add(1, Ordering::SeqCst);
+            if let Err(e) = self.context.spawn_worker() {
+                self.context.live_count.fetch_sub(1, Ordering::SeqCst);
+                return Err(PoolCreationError::Spawn(e));
+            }
+        }
+        Ok(())
+    }
+
+    /// 获取存活的工作线程数
+    pub fn size(&self) -> usize {
+        self.context.live_count.load(Ordering::SeqCst)
+    }
+
+    /// 获取核心线程数
+    pub fn core_size(&self) -> usize {
+        self.context.sizing.core.load(Ordering::SeqCst)
+    }
+
+    /// 获取最大线程数
+    pub fn max_size(&self) -> usize {
+        self.context.sizing.max.load(Ordering::SeqCst)
+    }
+
+    /// 获取活跃任务数
+    pub fn active_count(&self) -> usize {
+        self.context.active_count.load(Ordering::SeqCst)
+    }
+
+    /// 获取已完成任务数（不包括 panic 的任务）
+    pub fn completed_count(&self) -> usize {
+        self.context.completed_count.load(Ordering::SeqCst)
+    }
+
+    /// 获取 panic 的任务数
+    pub fn panicked_count(&self) -> usize {
+        self.context.panicked_count.load(Ordering::SeqCst)
+    }
+
+    /// 获取已提交任务数
+    pub fn submitted_count(&self) -> usize {
+        self.submitted_count.load(Ordering::SeqCst)
+    }
+
+    /// 获取没有执行就被丢弃的任务数（DropOldest 策略挤掉的，以及强制关闭时还在排队的）
+    pub fn dropped_count(&self) -> usize {
+        self.dropped_count.load(Ordering::SeqCst)
+    }
+
+    /// 获取等待中的任务数（全局队列和各个工作线程队列里的）
+    pub fn queued_count(&self) -> usize {
+        self.context.queue.len() + self.context.locals.len()
+    }
+
+    /// 某个优先级正在排队的任务数；工作线程自己队列里的任务都是普通优先级
+    pub fn queued_count_by_priority(&self, priority: Priority) -> usize {
+        let queued = self.context.queue.len_of(priority);
+        match priority {
+            Priority::Normal => queued + self.context.locals.len(),
+            Priority::High | Priority::Low => queued,
+        }
+    }
+
+    /// 优雅关闭线程池
+    /// 等待所有任务完成后再关闭
+    pub fn shutdown(&mut self) {
+        // 关闭队列，不再接受新任务，已经排队的任务继续执行
+        self.context.queue.close();
+
+        // 等待所有线程完成
+        self.context.join_workers();
+    }
+
+    /// 强制关闭线程池
+    /// 丢弃还在排队的任务，只等待正在执行的任务结束
+    pub fn shutdown_now(&mut self) {
+        // 关闭队列并丢弃还在排队的任务，它们的 JobHandle 会得到 JobError::Dropped
+        let mut pending = self.context.queue.close_and_drain();
+        pending.extend(self.context.locals.drain());
+        self.dropped_count
+            .fetch_add(pending.len(), Ordering::SeqCst);
+        drop(pending);
+
+        // 等待所有线程退出
+        self.context.join_workers();
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        if !self.context.queue.is_closed() {
+            self.shutdown();
+        }
+    }
+}
+
+/// 线程数的上下限以及缩容请求
+pub(crate) struct PoolSizing {
+    core: AtomicUsize,
+    max: AtomicUsize,
+    /// resize 缩容时还需要退出的线程数
+    retiring: AtomicUsize,
+    /// 超过核心线程数的线程空闲多久后退出
+    keep_alive: Duration,
+    /// 下一个工作线程的编号
+    next_id: AtomicUsize,
+}
+
+impl PoolSizing {
+    pub(crate) fn new(core: usize, max: usize, keep_alive: Duration) -> PoolSizing {
+        PoolSizing {
+            core: AtomicUsize::new(core),
+            max: AtomicUsize::new(max),
+            retiring: AtomicUsize::new(0),
+            keep_alive,
+            next_id: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// 所有工作线程共享的状态
+#[derive(Clone)]
+struct WorkerContext {
+    queue: Arc<JobQueue>,
+    /// 启动过的工作线程，包括已经退出、还没有回收的
+    workers: Arc<Mutex<Vec<Worker>>>,
+    /// 每个工作线程自己的任务队列
+    locals: Arc<LocalQueues>,
+    sizing: Arc<PoolSizing>,
+    /// 存活的工作线程数
+    live_count: Arc<AtomicUsize>,
+    active_count: Arc<AtomicUsize>,
+    completed_count: Arc<AtomicUsize>,
+    panicked_count: Arc<AtomicUsize>,
+    config: Arc<WorkerConfig>,
+}
+
+impl WorkerContext {
+    /// 启动一个新的工作线程，调用方已经把 live_count 加一
+    fn spawn_worker(&self) -> io::Result<()> {
+        let id = self.sizing.next_id.fetch_add(1, Ordering::SeqCst);
+        let mut workers = self.workers.lock().unwrap_or_else(PoisonError::into_inner);
+        // 顺便回收已经退出的线程
+        workers.retain_mut(|worker| !worker.reap());
+        workers.push(Worker::new(id, self.clone())?);
+        Ok(())
+    }
+
+    /// 排队的任务比空闲线程多、线程数没有达到上限时临时增加一个线程
+    fn grow(&self) {
+        let max = self.sizing.max.load(Ordering::SeqCst);
+        // 先做不加锁的检查，固定大小的线程池不用每次都去锁队列
+        if self.live_count.load(Ordering::SeqCst) >= max || !self.queue.is_starved() {
+            return;
+        }
+        let reserved = self
+            .live_count
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |live| {
+                (live < max).then_some(live + 1)
+            });
+        // 临时线程启动失败不影响任务，现有的线程会把它执行完
+        if reserved.is_ok() && self.spawn_worker().is_err() {
+            self.live_count.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// 有缩容请求时领取一个，当前线程随后退出
+    fn take_retirement(&self) -> bool {
+        let taken = self
+            .sizing
+            .retiring
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |retiring| {
+                retiring.checked_sub(1)
+            })
+            .is_ok();
+        if taken {
+            self.live_count.fetch_sub(1, Ordering::SeqCst);
+        }
+        taken
+    }
+
+    /// 空闲超时后，线程数多于核心线程数时当前线程退出
+    fn retire_idle(&self) -> bool {
+        let core = self.sizing.core.load(Ordering::SeqCst);
+        let retiring = self.sizing.retiring.load(Ordering::SeqCst);
+        self.live_count
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |live| {
+                (live > core + retiring).then(|| live - 1)
+            })
+            .is_ok()
+    }
+
+    /// 依次从自己的队列、全局队列和别的线程的队列里找任务，都没有时返回 None；
+    /// 自己队列里的任务都是普通优先级，全局队列里有高优先级（包括排队太久提升上来）的任务时先去全局队列取，
+    /// 这样不停提交普通任务的线程也不会把全局队列里的任务饿着
+    fn find_job(&self, id: usize, local: &LocalQueue) -> Option<Job> {
+        if self.queue.has_urgent()
+            && let Some(job) = self.queue.try_pop()
+        {
+            return Some(job);
+        }
+
+        let (job, more) = {
+            let mut jobs = local.lock();
+            (jobs.pop_front(), !jobs.is_empty())
+        };
+        if let Some(job) = job {
+            // 自己的队列里还有任务，叫醒空闲的线程来分担
+            if more {
+                self.queue.signal();
+            }
+            return Some(job);
+        }
+
+        self.queue
+            .try_pop()
+            .or_else(|| self.locals.steal(id, local))
+    }
+
+    /// 执行一个任务并更新计数，panic 只影响这一个任务
+    fn run_job(&self, job: Job) {
+        // 增加活跃计数
+        self.active_count.fetch_add(1, Ordering::SeqCst);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(job));
+
+        // 减少活跃计数，增加完成或 panic 计数
+        self.active_count.fetch_sub(1, Ordering::SeqCst);
+        match result {
+            Ok(()) => {
+                self.completed_count.fetch_add(1, Ordering::SeqCst);
+            }
+            Err(payload) => {
+                // panic 的消息已经由标准库的 panic hook 输出，这里只计数并交给 panic 处理函数
+                self.panicked_count.fetch_add(1, Ordering::SeqCst);
+                if let Some(handler) = &self.config.panic_handler {
+                    run_callback(|| handler(payload));
+                }
+            }
+        }
+    }
+
+    /// 找不到任务时先让出几次 CPU 再找，新任务很快到来时就不用睡下再被叫醒
+    fn spin(&self, id: usize, local: &LocalQueue) -> Option<Job> {
+        (0..SPIN_ROUNDS).find_map(|_| {
+            thread::yield_now();
+            self.find_job(id, local)
+        })
+    }
+
+    /// 等待所有线程退出，队列必须已经关闭
+    fn join_workers(&self) {
+        loop {
+            let workers =
+                std::mem::take(&mut *self.workers.lock().unwrap_or_else(PoisonError::into_inner));
+            if workers.is_empty() {
+                break;
+            }
+            for mut worker in workers {
+                worker.join();
+            }
+        }
+    }
+}
+
+/// 调用构建器设置的回调；回调 panic 时忽略（消息已经由标准库的 panic hook 输出），
+/// 不让工作线程因此退出，否则重启后的线程会再次调用回调、反复重启
+fn run_callback(callback: impl FnOnce()) {
+    let _ = panic::catch_unwind(AssertUnwindSafe(callback));
+}
+
+/// 工作线程
+struct Worker {
+    /// 线程意外退出时会在这里换上重新启动的线程
+    thread: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
+}
+
+impl Worker {
+    fn new(id: usize, context: WorkerContext) -> io::Result<Worker> {
+        let thread = Arc::new(Mutex::new(None));
+        let local = context.locals.register(id);
+        if let Err(e) = Worker::spawn(id, context.clone(), local, Arc::clone(&thread)) {
+            context.locals.unregister(id);
+            return Err(e);
+        }
+
+        Ok(Worker { thread })
+    }
+
+    /// 启动线程并把句柄放进 slot
+    /// 重新启动时沿用原来的任务队列，里面的任务不会丢
+    fn spawn(
+        id: usize,
+        context: WorkerContext,
+        local: Arc<LocalQueue>,
+        slot: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
+    ) -> io::Result<()> {
+        // 持有锁直到句柄放好，避免新线程立刻退出时重启的句柄被覆盖
+        let mut handle = slot.lock().unwrap_or_else(PoisonError::into_inner);
+        let sentinel = Sentinel {
+            id,
+            context: context.clone(),
+            local: Arc::clone(&local),
+            slot: Arc::clone(&slot),
+        };
+
+        let mut builder = thread::Builder::new();
+        if let Some(prefix) = &context.config.thread_name {
+            builder = builder.name(format!("{}-{}", prefix, id));
+        }
+        if let Some(stack_size) = context.config.stack_size {
+            builder = builder.stack_size(stack_size);
+        }
+
+        *handle = Some(builder.spawn(move || {
+            let _sentinel = sentinel;
+            Worker::run(id, context, local);
+        })?);
+        Ok(())
+    }
+
+    fn run(id: usize, context: WorkerContext, local: Arc<LocalQueue>) {
+        steal::enter(&context.locals, id, Arc::clone(&local));
+        if let Some(hook) = &context.config.on_thread_start {
+            run_callback(|| hook(id));
+        }
+
+        loop {
+            // 手上还有任务时不响应缩容，做完再说
+            if local.is_empty() && context.take_retirement() {
+                break;
+            }
+
+            let stamp = context.queue.local_stamp();
+            let job = match context
+                .find_job(id, &local)
+                .or_else(|| context.spin(id, &local))
+            {
+                Some(job) => job,
+                None => match context.queue.pop(context.sizing.keep_alive, stamp) {
+                    Popped::Job(job) => job,
+                    Popped::TimedOut if context.retire_idle() => break,
+                    Popped::TimedOut | Popped::Woken => continue,
+                    Popped::Closed => {
+                        context.live_count.fetch_sub(1, Ordering::SeqCst);
+                        break;
+                    }
+                },
+            };
+
+            context.run_job(job);
+        }
+
+        context.locals.unregister(id);
+        steal::leave();
+        if let Some(hook) = &context.config.on_thread_exit {
+            run_callback(|| hook(id));
+        }
+    }
+
+    /// 线程已经退出时回收它，返回是否回收了
+    fn reap(&mut self) -> bool {
+        let mut slot = self.thread.lock().unwrap_or_else(PoisonError::into_inner);
+        if !slot.as_ref().is_none_or(|handle| handle.is_finished()) {
+            return false;
+        }
+        // 线程 panic 的消息已经由标准库的 panic hook 输出，这里只回收
+        if let Some(handle) = slot.take() {
+            let _ = handle.join();
+        }
+        true
+    }
+
+    /// 等待线程退出；线程退出前重启过的话，继续等待新的线程
+    fn join(&mut self) {
+        loop {
+            let handle = self
+                .thread
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take();
+            let Some(handle) = handle else {
+                break;
+            };
+            let _ = handle.join();
+        }
+    }
+}
+
+/// 随工作线程一起销毁；线程因为 panic 退出时重新启动一个工作线程
+struct Sentinel {
+    id: usize,
+    context: WorkerContext,
+    local: Arc<LocalQueue>,
+    slot: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
+}
+
+impl Drop for Sentinel {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            let local = Arc::clone(&self.local);
+            let slot = Arc::clone(&self.slot);
+            if Worker::spawn(self.id, self.context.clone(), local, slot).is_err() {
+                self.context.locals.unregister(self.id);
+                // 重启失败就少一个线程，之后的 grow 或 resize 还能补上
+                self.context.live_count.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+    }
+}
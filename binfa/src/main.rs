@@ -1,269 +1,5 @@
-use std::fmt;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex, mpsc};
+use binfa::{Priority, RejectionPolicy, ThreadPool};
 use std::thread;
-
-/// 线程池创建错误
-#[derive(Debug)]
-pub enum PoolCreationError {
-    ZeroSize,
-    TooLarge(usize),
-}
-
-impl fmt::Display for PoolCreationError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            PoolCreationError::ZeroSize => write!(f, "线程池大小不能为0"),
-            PoolCreationError::TooLarge(size) => {
-                write!(f, "线程池大小 {} 超过最大限制 1000", size)
-            }
-        }
-    }
-}
-
-impl std::error::Error for PoolCreationError {}
-
-/// 任务类型
-type Job = Box<dyn FnOnce() + Send + 'static>;
-
-/// 工作线程接收的消息
-enum Message {
-    /// 新任务
-    NewJob(Job),
-    /// 终止信号
-    Terminate,
-}
-
-/// 线程池
-pub struct ThreadPool {
-    /// 工作线程
-    workers: Vec<Worker>,
-
-    /// 任务发送端
-    sender: Option<mpsc::Sender<Message>>,
-
-    /// 活跃任务计数
-    active_count: Arc<AtomicUsize>,
-
-    /// 完成任务计数
-    completed_count: Arc<AtomicUsize>,
-
-    /// 总提交任务计数
-    submitted_count: Arc<AtomicUsize>,
-}
-
-impl ThreadPool {
-    /// 创建线程池（panic 版本，用于简单场景）
-    pub fn new(size: usize) -> ThreadPool {
-        ThreadPool::build(size).expect("创建线程池失败")
-    }
-
-    /// 创建线程池（Result 版本，用于需要错误处理的场景）
-    pub fn build(size: usize) -> Result<ThreadPool, PoolCreationError> {
-        if size == 0 {
-            return Err(PoolCreationError::ZeroSize);
-        }
-
-        if size > 1000 {
-            return Err(PoolCreationError::TooLarge(size));
-        }
-
-        let (sender, receiver) = mpsc::channel();
-        let receiver = Arc::new(Mutex::new(receiver));
-
-        let active_count = Arc::new(AtomicUsize::new(0));
-        let completed_count = Arc::new(AtomicUsize::new(0));
-        let submitted_count = Arc::new(AtomicUsize::new(0));
-
-        let mut workers = Vec::with_capacity(size);
-
-        for id in 0..size {
-            workers.push(Worker::new(
-                id,
-                Arc::clone(&receiver),
-                Arc::clone(&active_count),
-                Arc::clone(&completed_count),
-            ));
-        }
-
-        Ok(ThreadPool {
-            workers,
-            sender: Some(sender),
-            active_count,
-            completed_count,
-            submitted_count,
-        })
-    }
-
-    /// 执行任务
-    pub fn execute<F>(&self, f: F)
-    where
-        F: FnOnce() + Send + 'static,
-    {
-        let job = Box::new(f);
-
-        self.submitted_count.fetch_add(1, Ordering::SeqCst);
-
-        self.sender
-            .as_ref()
-            .expect("线程池已关闭")
-            .send(Message::NewJob(job))
-            .expect("发送任务失败");
-    }
-
-    /// 执行任务并返回结果
-    pub fn execute_with_result<F, T>(&self, f: F) -> mpsc::Receiver<T>
-    where
-        F: FnOnce() -> T + Send + 'static,
-        T: Send + 'static,
-    {
-        let (tx, rx) = mpsc::channel();
-
-        self.execute(move || {
-            let result = f();
-            let _ = tx.send(result);
-        });
-
-        rx
-    }
-
-    /// 获取工作线程数
-    pub fn size(&self) -> usize {
-        self.workers.len()
-    }
-
-    /// 获取活跃任务数
-    pub fn active_count(&self) -> usize {
-        self.active_count.load(Ordering::SeqCst)
-    }
-
-    /// 获取已完成任务数
-    pub fn completed_count(&self) -> usize {
-        self.completed_count.load(Ordering::SeqCst)
-    }
-
-    /// 获取已提交任务数
-    pub fn submitted_count(&self) -> usize {
-        self.submitted_count.load(Ordering::SeqCst)
-    }
-
-    /// 获取等待中的任务数
-    pub fn queued_count(&self) -> usize {
-        let submitted = self.submitted_count.load(Ordering::SeqCst);
-        let completed = self.completed_count.load(Ordering::SeqCst);
-        let active = self.active_count.load(Ordering::SeqCst);
-        submitted.saturating_sub(completed + active)
-    }
-
-    /// 优雅关闭线程池
-    /// 等待所有任务完成后再关闭
-    pub fn shutdown(&mut self) {
-        println!("正在关闭线程池...");
-
-        // 关闭发送端，不再接受新任务
-        drop(self.sender.take());
-
-        // 等待所有线程完成
-        for worker in &mut self.workers {
-            println!("关闭 worker {}", worker.id);
-
-            if let Some(thread) = worker.thread.take() {
-                thread.join().unwrap();
-            }
-        }
-
-        println!("线程池已关闭");
-    }
-
-    /// 强制关闭线程池
-    /// 立即停止所有线程，不等待任务完成
-    pub fn shutdown_now(&mut self) {
-        println!("强制关闭线程池...");
-
-        // 向所有 worker 发送终止信号
-        for _ in &self.workers {
-            self.sender
-                .as_ref()
-                .unwrap()
-                .send(Message::Terminate)
-                .unwrap();
-        }
-
-        // 关闭发送端
-        drop(self.sender.take());
-
-        // 等待所有线程退出
-        for worker in &mut self.workers {
-            if let Some(thread) = worker.thread.take() {
-                thread.join().unwrap();
-            }
-        }
-
-        println!("线程池已强制关闭");
-    }
-}
-
-impl Drop for ThreadPool {
-    fn drop(&mut self) {
-        if self.sender.is_some() {
-            self.shutdown();
-        }
-    }
-}
-
-/// 工作线程
-struct Worker {
-    id: usize,
-    thread: Option<thread::JoinHandle<()>>,
-}
-
-impl Worker {
-    fn new(
-        id: usize,
-        receiver: Arc<Mutex<mpsc::Receiver<Message>>>,
-        active_count: Arc<AtomicUsize>,
-        completed_count: Arc<AtomicUsize>,
-    ) -> Worker {
-        let thread = thread::spawn(move || {
-            loop {
-                // 接收消息
-                let message = receiver.lock().expect("获取锁失败").recv();
-
-                match message {
-                    Ok(Message::NewJob(job)) => {
-                        println!("Worker {} 开始执行任务", id);
-
-                        // 增加活跃计数
-                        active_count.fetch_add(1, Ordering::SeqCst);
-
-                        // 执行任务
-                        job();
-
-                        // 减少活跃计数，增加完成计数
-                        active_count.fetch_sub(1, Ordering::SeqCst);
-                        completed_count.fetch_add(1, Ordering::SeqCst);
-
-                        println!("Worker {} 完成任务", id);
-                    }
-                    Ok(Message::Terminate) => {
-                        println!("Worker {} 收到终止信号", id);
-                        break;
-                    }
-                    Err(_) => {
-                        println!("Worker {} 发送端已关闭，退出", id);
-                        break;
-                    }
-                }
-            }
-        });
-
-        Worker {
-            id,
-            thread: Some(thread),
-        }
-    }
-}
-
 use std::time::Duration;
 
 fn main() {
@@ -280,13 +16,15 @@ fn main() {
         }
 
         // pool 在作用域结束时自动调用 drop，优雅关闭
+        println!("正在关闭线程池...");
     }
+    println!("线程池已关闭");
 
     println!("\n=== 示例2：带返回值的任务 ===");
     {
         let pool = ThreadPool::new(2);
 
-        let receivers: Vec<_> = (0..5)
+        let handles: Vec<_> = (0..5)
             .map(|i| {
                 pool.execute_with_result(move || {
                     thread::sleep(Duration::from_millis(100));
@@ -295,8 +33,8 @@ fn main() {
             })
             .collect();
 
-        for (i, rx) in receivers.into_iter().enumerate() {
-            let result = rx.recv().unwrap();
+        for (i, handle) in handles.into_iter().enumerate() {
+            let result = handle.join().expect("任务不应该失败");
             println!("任务 {} 的结果：{}", i, result);
         }
     }
@@ -340,7 +78,23 @@ fn main() {
         }
     }
 
-    println!("\n=== 示例5：强制关闭 ===");
+    println!("\n=== 示例5：任务 panic ===");
+    {
+        let pool = ThreadPool::new(2);
+
+        let handle = pool.execute_with_result(|| -> u32 { panic!("任务出错了") });
+        match handle.join() {
+            Ok(_) => println!("不应该成功"),
+            Err(e) => println!("预期的错误：{}", e),
+        }
+
+        // panic 不会影响 worker，后面的任务照常执行
+        let handle = pool.execute_with_result(|| 1 + 1);
+        println!("panic 之后的结果：{}", handle.join().unwrap());
+        println!("panic 任务数：{}", pool.panicked_count());
+    }
+
+    println!("\n=== 示例6：强制关闭 ===");
     {
         let mut pool = ThreadPool::new(2);
 
@@ -352,6 +106,104 @@ fn main() {
         }
 
         thread::sleep(Duration::from_millis(100));
-        pool.shutdown_now(); // 立即关闭，不等待任务完成
+        println!("强制关闭线程池...");
+        pool.shutdown_now(); // 立即关闭，丢弃还在排队的任务
+        println!("线程池已强制关闭，丢弃了 {} 个任务", pool.dropped_count());
+    }
+
+    println!("\n=== 示例7：有界队列 ===");
+    {
+        let pool = ThreadPool::bounded(1, 2, RejectionPolicy::Fail).expect("创建线程池失败");
+
+        for i in 0..5 {
+            let result = pool.try_execute(move || {
+                thread::sleep(Duration::from_millis(100));
+                println!("任务 {} 完成", i);
+            });
+            match result {
+                Ok(()) => println!("任务 {} 已提交", i),
+                Err(e) => println!("任务 {} 被拒绝：{}", i, e),
+            }
+        }
+    }
+
+    println!("\n=== 示例8：动态线程数 ===");
+    {
+        let pool = ThreadPool::builder()
+            .num_threads(1)
+            .max_threads(4)
+            .keep_alive(Duration::from_millis(200))
+            .build()
+            .expect("创建线程池失败");
+
+        for i in 0..8 {
+            pool.execute(move || {
+                thread::sleep(Duration::from_millis(100));
+                println!("任务 {} 完成", i);
+            });
+        }
+        println!("突发负载下的线程数：{}", pool.size());
+
+        thread::sleep(Duration::from_millis(800));
+        println!("空闲一段时间后的线程数：{}", pool.size());
+
+        pool.resize(3).expect("调整线程数失败");
+        println!("resize(3) 之后的线程数：{}", pool.size());
+    }
+
+    println!("\n=== 示例9：作用域任务 ===");
+    {
+        let pool = ThreadPool::new(4);
+        let mut words = vec![
+            String::from("线程"),
+            String::from("池"),
+            String::from("作用域"),
+        ];
+
+        // 任务直接借用 words，不需要 Arc
+        pool.scope(|s| {
+            for word in words.iter_mut() {
+                s.spawn(move || word.push('!'));
+            }
+        });
+        println!("作用域结束后的数据：{:?}", words);
+    }
+
+    println!("\n=== 示例10：并行迭代 ===");
+    {
+        let pool = ThreadPool::new(4);
+
+        let squares = pool.map(1..=8, |n| n * n);
+        println!("map 的结果（保持输入顺序）：{:?}", squares);
+
+        let sum = pool.map_reduce(1..=100, |n| n, |a, b| a + b);
+        println!("map_reduce 求和：{:?}", sum);
+
+        let mut numbers = vec![42, 7, 19, 3, 88, 1, 56, 23];
+        pool.par_sort(&mut numbers);
+        println!("par_sort 之后：{:?}", numbers);
+    }
+
+    println!("\n=== 示例11：任务优先级 ===");
+    {
+        let pool = ThreadPool::new(1);
+
+        // 先占住唯一的线程，让后面的任务都排队
+        pool.execute(|| thread::sleep(Duration::from_millis(100)));
+        thread::sleep(Duration::from_millis(10));
+        for (priority, name) in [
+            (Priority::Low, "低"),
+            (Priority::Normal, "普通"),
+            (Priority::High, "高"),
+        ] {
+            pool.execute_with_priority(priority, move || println!("{}优先级任务执行", name));
+        }
+        for priority in Priority::ALL {
+            println!(
+                "{:?} 优先级排队中：{}",
+                priority,
+                pool.queued_count_by_priority(priority)
+            );
+        }
     }
 }
@@ -0,0 +1,137 @@
+/*
+在这个文件里面实现线程池上的并行迭代辅助函数
+map、for_each、map_reduce 把输入切成若干块，每块作为一个作用域任务交给工作线程，结果按输入顺序拼回来；
+par_sort 先并行排序每一块，再一轮轮并行归并相邻的两块
+*/
+use crate::ThreadPool;
+
+/// 每个线程大约分到几块，块多一些忙闲更均匀
+const CHUNKS_PER_THREAD: usize = 4;
+
+impl ThreadPool {
+    /// 输入切成多少块
+    fn chunk_count(&self, len: usize) -> usize {
+        (self.size().max(1) * CHUNKS_PER_THREAD).min(len).max(1)
+    }
+
+    /// 把输入按顺序切成大致等长的若干块
+    fn split<T>(&self, items: Vec<T>) -> Vec<Vec<T>> {
+        let chunk_len = items.len().div_ceil(self.chunk_count(items.len())).max(1);
+        let mut items = items.into_iter();
+        let mut chunks = Vec::new();
+        loop {
+            let chunk: Vec<T> = items.by_ref().take(chunk_len).collect();
+            if chunk.is_empty() {
+                break chunks;
+            }
+            chunks.push(chunk);
+        }
+    }
+
+    /// 并行地对每个元素调用 `f`，结果的顺序和输入一致
+    ///
+    /// ```
+    /// use binfa::ThreadPool;
+    ///
+    /// let pool = ThreadPool::build(2).expect("创建线程池失败");
+    /// let squares = pool.map(1..=5, |n| n * n);
+    /// assert_eq!(squares, vec![1, 4, 9, 16, 25]);
+    /// ```
+    pub fn map<I, F, T>(&self, iter: I, f: F) -> Vec<T>
+    where
+        I: IntoIterator,
+        I::Item: Send,
+        F: Fn(I::Item) -> T + Sync,
+        T: Send,
+    {
+        let chunks = self.split(iter.into_iter().collect());
+        let mut results: Vec<Vec<T>> = chunks.iter().map(|_| Vec::new()).collect();
+        let f = &f;
+        self.scope(|s| {
+            for (chunk, result) in chunks.into_iter().zip(results.iter_mut()) {
+                s.spawn(move || *result = chunk.into_iter().map(f).collect());
+            }
+        });
+        results.into_iter().flatten().collect()
+    }
+
+    /// 并行地对每个元素调用 `f`，所有调用结束后返回
+    pub fn for_each<I, F>(&self, iter: I, f: F)
+    where
+        I: IntoIterator,
+        I::Item: Send,
+        F: Fn(I::Item) + Sync,
+    {
+        let f = &f;
+        self.scope(|s| {
+            for chunk in self.split(iter.into_iter().collect()) {
+                s.spawn(move || chunk.into_iter().for_each(f));
+            }
+        });
+    }
+
+    /// 并行地用 `map` 变换每个元素，再按输入顺序用 `reduce` 合并，输入为空时返回 None
+    ///
+    /// `reduce` 需要满足结合律，不要求交换律
+    ///
+    /// ```
+    /// use binfa::ThreadPool;
+    ///
+    /// let pool = ThreadPool::build(2).expect("创建线程池失败");
+    /// let words = ["线", "程", "池"];
+    /// let joined = pool.map_reduce(words, String::from, |a, b| a + &b);
+    /// assert_eq!(joined.as_deref(), Some("线程池"));
+    /// ```
+    pub fn map_reduce<I, M, R, T>(&self, iter: I, map: M, reduce: R) -> Option<T>
+    where
+        I: IntoIterator,
+        I::Item: Send,
+        M: Fn(I::Item) -> T + Sync,
+        R: Fn(T, T) -> T + Sync,
+        T: Send,
+    {
+        let chunks = self.split(iter.into_iter().collect());
+        let mut partials: Vec<Option<T>> = chunks.iter().map(|_| None).collect();
+        let (map, reduce) = (&map, &reduce);
+        self.scope(|s| {
+            for (chunk, partial) in chunks.into_iter().zip(partials.iter_mut()) {
+                s.spawn(move || *partial = chunk.into_iter().map(map).reduce(reduce));
+            }
+        });
+        partials.into_iter().flatten().reduce(reduce)
+    }
+
+    /// 并行的稳定排序：先把切片分块并行排序，再一轮轮并行归并相邻的两块
+    ///
+    /// ```
+    /// use binfa::ThreadPool;
+    ///
+    /// let pool = ThreadPool::build(2).expect("创建线程池失败");
+    /// let mut numbers = vec![5, 3, 9, 1, 7];
+    /// pool.par_sort(&mut numbers);
+    /// assert_eq!(numbers, vec![1, 3, 5, 7, 9]);
+    /// ```
+    pub fn par_sort<T>(&self, slice: &mut [T])
+    where
+        T: Ord + Send,
+    {
+        let mut run = slice.len().div_ceil(self.chunk_count(slice.len())).max(1);
+        self.scope(|s| {
+            for chunk in slice.chunks_mut(run) {
+                s.spawn(move || chunk.sort());
+            }
+        });
+
+        // 标准库的 sort 能识别已经有序的片段，两段有序的数据拼在一起再 sort 只需要一次归并
+        while run < slice.len() {
+            self.scope(|s| {
+                for pair in slice.chunks_mut(run * 2) {
+                    if pair.len() > run {
+                        s.spawn(move || pair.sort());
+                    }
+                }
+            });
+            run *= 2;
+        }
+    }
+}
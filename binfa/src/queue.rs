@@ -0,0 +1,430 @@
+/*
+在这个文件里面实现线程池的任务队列
+JobQueue：Mutex<VecDeque> 加两个 Condvar，可以限制容量；
+队列满时按 RejectionPolicy 处理新任务：阻塞等待、返回 QueueFull、由提交者自己执行或丢弃最早的任务；
+队列还记录有多少线程在等任务，线程池据此判断是否需要临时增加线程；
+开启工作窃取后它是全局队列，从外部提交的任务都在这里排队，按先来后到一个一个取；
+任务分高、普通、低三个优先级，先取优先级高的，排队太久的任务逐级提升优先级，不会一直饿着
+*/
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// 任务类型
+pub(crate) type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// 任务的优先级
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub enum Priority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+impl Priority {
+    /// 从高到低的所有优先级
+    pub const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Low];
+
+    fn level(self) -> usize {
+        self as usize
+    }
+}
+
+/// 队列里的任务和它入队的时间
+struct Queued {
+    job: Job,
+    since: Instant,
+}
+
+/// 队列满时如何处理新提交的任务
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum RejectionPolicy {
+    /// 阻塞提交者，直到队列有空位
+    #[default]
+    Block,
+    /// 拒绝新任务，返回 `SubmitError::QueueFull`
+    Fail,
+    /// 由提交任务的线程自己执行新任务
+    CallerRuns,
+    /// 丢弃队列中优先级最低、最早的任务，把新任务放进队列
+    DropOldest,
+}
+
+/// 提交任务失败的原因
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SubmitError {
+    /// 队列已满
+    QueueFull,
+    /// 线程池已关闭
+    ShutDown,
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SubmitError::QueueFull => write!(f, "任务队列已满"),
+            SubmitError::ShutDown => write!(f, "线程池已关闭"),
+        }
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+/// 放入队列的结果
+pub(crate) enum Pushed {
+    /// 新任务已经进入队列
+    Queued,
+    /// 新任务已经进入队列，挤掉了最早的任务
+    Evicted(Job),
+    /// 队列已满，新任务交还给提交者执行
+    RunHere(Job),
+}
+
+/// 取任务的结果
+pub(crate) enum Popped {
+    Job(Job),
+    /// 等待超时，没有取到任务
+    TimedOut,
+    /// 被 wake_all 或 signal 唤醒，没有取到任务
+    Woken,
+    /// 队列已关闭并且已经取空
+    Closed,
+}
+
+struct QueueState {
+    /// 按优先级从高到低，每个优先级一个先进先出的队列
+    jobs: [VecDeque<Queued>; 3],
+    closed: bool,
+    /// 每次 wake_all 加一，等待中的线程据此判断是否被唤醒
+    generation: u64,
+    /// signal 发出、还没有被等待线程领取的唤醒次数
+    signals: usize,
+}
+
+impl QueueState {
+    fn len(&self) -> usize {
+        self.jobs.iter().map(VecDeque::len).sum()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.jobs.iter().all(VecDeque::is_empty)
+    }
+
+    /// 下一个该执行的任务：每个队列的队头等待每满一个 `aging` 就提升一级，
+    /// 提升后级别相同的，先执行等得久的
+    fn pop_next(&mut self, aging: Duration) -> Option<Job> {
+        // 只有一个优先级有任务时不用比较，省掉读时钟
+        let mut levels = (0..self.jobs.len()).filter(|&level| !self.jobs[level].is_empty());
+        let first = levels.next()?;
+        if levels.next().is_none() {
+            return self.jobs[first].pop_front().map(|queued| queued.job);
+        }
+
+        let now = Instant::now();
+        let level = (0..self.jobs.len())
+            .filter_map(|level| {
+                let front = self.jobs[level].front()?;
+                let waited = now.saturating_duration_since(front.since);
+                let boost = waited.as_nanos() / aging.as_nanos().max(1);
+                let effective = (level as u128).saturating_sub(boost);
+                Some((effective, front.since, level))
+            })
+            .min()?
+            .2;
+        self.jobs[level].pop_front().map(|queued| queued.job)
+    }
+
+    /// 优先级最低、最早入队的任务
+    fn pop_lowest(&mut self) -> Option<Job> {
+        self.jobs
+            .iter_mut()
+            .rev()
+            .find_map(VecDeque::pop_front)
+            .map(|queued| queued.job)
+    }
+}
+
+/// 线程池的任务队列，capacity 为 None 时不限容量
+pub(crate) struct JobQueue {
+    state: Mutex<QueueState>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    /// 正在等待任务的线程数，只在持有锁时修改，不加锁读取时只作参考
+    waiting: AtomicUsize,
+    /// signal 被调用的次数，即使当时没有线程在等也会加一；
+    /// 正要睡下的线程据此发现工作线程队列里来了新任务
+    local_pushes: AtomicUsize,
+    /// 最早什么时候有任务提升到高优先级（从 epoch 起的纳秒数，没有任务时为 u64::MAX），
+    /// 持有锁时更新，工作线程不加锁读取
+    urgent_at: AtomicU64,
+    epoch: Instant,
+    capacity: Option<usize>,
+    policy: RejectionPolicy,
+    /// 任务每等待这么久提升一级优先级
+    aging: Duration,
+}
+
+impl JobQueue {
+    pub(crate) fn new(
+        capacity: Option<usize>,
+        policy: RejectionPolicy,
+        aging: Duration,
+    ) -> JobQueue {
+        JobQueue {
+            state: Mutex::new(QueueState {
+                jobs: Default::default(),
+                closed: false,
+                generation: 0,
+                signals: 0,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            waiting: AtomicUsize::new(0),
+            local_pushes: AtomicUsize::new(0),
+            urgent_at: AtomicU64::new(u64::MAX),
+            epoch: Instant::now(),
+            capacity,
+            policy,
+            aging,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 队列内容变化后更新 urgent_at：每个队列的队头排队满 级别×aging 时提升到高优先级
+    fn update_urgent(&self, state: &QueueState) {
+        let urgent_at = state
+            .jobs
+            .iter()
+            .enumerate()
+            .filter_map(|(level, jobs)| {
+                let since = jobs.front()?.since;
+                let urgent = since + self.aging.saturating_mul(level as u32);
+                Some(urgent.saturating_duration_since(self.epoch).as_nanos() as u64)
+            })
+            .min()
+            .unwrap_or(u64::MAX);
+        self.urgent_at.store(urgent_at, Ordering::SeqCst);
+    }
+
+    /// 不限容量的队列才允许任务放进工作线程自己的队列，否则容量限制就不准了
+    pub(crate) fn is_unbounded(&self) -> bool {
+        self.capacity.is_none()
+    }
+
+    fn is_full(&self, state: &QueueState) -> bool {
+        self.capacity
+            .is_some_and(|capacity| state.len() >= capacity)
+    }
+
+    /// 按拒绝策略放入任务；`wait` 为 false 时 Block 策略也不等待，直接返回 QueueFull
+    pub(crate) fn push(
+        &self,
+        job: Job,
+        priority: Priority,
+        wait: bool,
+    ) -> Result<Pushed, SubmitError> {
+        let mut state = self.lock();
+        if state.closed {
+            return Err(SubmitError::ShutDown);
+        }
+
+        let mut evicted = None;
+        if self.is_full(&state) {
+            match self.policy {
+                RejectionPolicy::Block if wait => {
+                    state = self
+                        .not_full
+                        .wait_while(state, |state| self.is_full(state) && !state.closed)
+                        .unwrap_or_else(PoisonError::into_inner);
+                    if state.closed {
+                        return Err(SubmitError::ShutDown);
+                    }
+                }
+                RejectionPolicy::Block | RejectionPolicy::Fail => {
+                    return Err(SubmitError::QueueFull);
+                }
+                RejectionPolicy::CallerRuns => return Ok(Pushed::RunHere(job)),
+                RejectionPolicy::DropOldest => evicted = state.pop_lowest(),
+            }
+        }
+
+        state.jobs[priority.level()].push_back(Queued {
+            job,
+            since: Instant::now(),
+        });
+        self.update_urgent(&state);
+        // 没有线程在等时不必 notify，省掉一次系统调用
+        if self.waiting.load(Ordering::SeqCst) > 0 {
+            self.not_empty.notify_one();
+        }
+        // 被挤掉的任务交给调用方在锁外丢弃
+        Ok(evicted.map_or(Pushed::Queued, Pushed::Evicted))
+    }
+
+    /// 当前的 signal 计数，工作线程找任务之前先记下来，交给 [`JobQueue::pop`]
+    pub(crate) fn local_stamp(&self) -> usize {
+        self.local_pushes.load(Ordering::SeqCst)
+    }
+
+    /// 取出下一个任务，最多等待 `timeout`
+    ///
+    /// `stamp` 是调用方上次找任务之前的 [`JobQueue::local_stamp`]；之后又有 signal 的话不睡下，
+    /// 否则在找完任务、还没开始等的这段时间里放进工作线程队列的任务要等到超时才有人偷
+    pub(crate) fn pop(&self, timeout: Duration, stamp: usize) -> Popped {
+        let state = self.lock();
+        let generation = state.generation;
+        // 先登记在等，再检查 signal 计数：signal 那边先加计数再看有没有人在等，两边至少有一边能看到对方
+        self.waiting.fetch_add(1, Ordering::SeqCst);
+        let (mut state, _) = self
+            .not_empty
+            .wait_timeout_while(state, timeout, |state| {
+                state.is_empty()
+                    && !state.closed
+                    && state.generation == generation
+                    && state.signals == 0
+                    && self.local_pushes.load(Ordering::SeqCst) == stamp
+            })
+            .unwrap_or_else(PoisonError::into_inner);
+        self.waiting.fetch_sub(1, Ordering::SeqCst);
+
+        if let Some(job) = state.pop_next(self.aging) {
+            self.update_urgent(&state);
+            self.notify_not_full();
+            Popped::Job(job)
+        } else if state.closed {
+            Popped::Closed
+        } else if state.signals > 0 {
+            state.signals -= 1;
+            Popped::Woken
+        } else if state.generation != generation || self.local_stamp() != stamp {
+            Popped::Woken
+        } else {
+            Popped::TimedOut
+        }
+    }
+
+    /// 不等待地取一个任务
+    pub(crate) fn try_pop(&self) -> Option<Job> {
+        let mut state = self.lock();
+        let job = state.pop_next(self.aging)?;
+        self.update_urgent(&state);
+        self.notify_not_full();
+        Some(job)
+    }
+
+    /// 是否有高优先级（包括排队太久提升上来）的任务在排队，不加锁读取，只作参考
+    pub(crate) fn has_urgent(&self) -> bool {
+        let urgent_at = self.urgent_at.load(Ordering::SeqCst);
+        // 队列空着时不用读时钟
+        urgent_at != u64::MAX && self.epoch.elapsed().as_nanos() as u64 >= urgent_at
+    }
+
+    /// 只有限制容量的队列才会有提交者在等空位
+    fn notify_not_full(&self) {
+        if !self.is_unbounded() {
+            self.not_full.notify_one();
+        }
+    }
+
+    /// 工作线程自己的队列里来了新任务：有线程在等任务时叫醒一个，让它去偷；
+    /// 没有线程在等时只把计数加一，正要睡下的线程看到计数变了会再找一轮
+    pub(crate) fn signal(&self) {
+        self.local_pushes.fetch_add(1, Ordering::SeqCst);
+        if self.waiting.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+        let mut state = self.lock();
+        self.post_signals(&mut state, 1);
+    }
+
+    fn post_signals(&self, state: &mut QueueState, count: usize) {
+        let waiting = self.waiting.load(Ordering::SeqCst);
+        let count = count.min(waiting.saturating_sub(state.signals));
+        state.signals += count;
+        for _ in 0..count {
+            self.not_empty.notify_one();
+        }
+    }
+
+    /// 唤醒所有等待任务的线程，让它们检查是否需要退出
+    pub(crate) fn wake_all(&self) {
+        self.lock().generation += 1;
+        self.not_empty.notify_all();
+    }
+
+    /// 排队的任务是否比等待任务的线程多
+    pub(crate) fn is_starved(&self) -> bool {
+        let state = self.lock();
+        state.len() > self.waiting.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// 某个优先级排队的任务数
+    pub(crate) fn len_of(&self, priority: Priority) -> usize {
+        self.lock().jobs[priority.level()].len()
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    /// 不再接受新任务，已经排队的任务仍会被取走执行
+    pub(crate) fn close(&self) {
+        self.lock().closed = true;
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    /// 关闭队列并取出所有还没执行的任务
+    pub(crate) fn close_and_drain(&self) -> Vec<Job> {
+        let jobs = {
+            let mut state = self.lock();
+            state.closed = true;
+            let jobs = state.jobs.iter_mut().flat_map(|jobs| jobs.drain(..));
+            let jobs = jobs.map(|queued| queued.job).collect();
+            self.update_urgent(&state);
+            jobs
+        };
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+        jobs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signal_before_waiting_is_not_lost() {
+        let queue = JobQueue::new(None, RejectionPolicy::Block, Duration::from_secs(1));
+
+        // 工作线程找完任务、还没开始等的时候，另一个线程往自己的队列放了任务并 signal，
+        // 这时还没有线程在等，signal 不会叫醒谁，之后睡下的线程必须自己发现
+        let stamp = queue.local_stamp();
+        queue.signal();
+
+        let start = Instant::now();
+        assert!(matches!(
+            queue.pop(Duration::from_secs(10), stamp),
+            Popped::Woken
+        ));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // 之后没有新的 signal，照常等到超时
+        let stamp = queue.local_stamp();
+        assert!(matches!(
+            queue.pop(Duration::from_millis(10), stamp),
+            Popped::TimedOut
+        ));
+    }
+}
@@ -0,0 +1,207 @@
+/*
+在这个文件里面实现作用域任务
+Scope：ThreadPool::scope 的闭包拿到的作用域，spawn 的任务可以借用调用者栈上的数据，
+scope 返回前一定等所有任务执行完；任务由线程池现有的工作线程执行，
+在工作线程上调用 scope 时，等待期间这个线程也会帮忙执行任务，不会把自己卡死
+*/
+use crate::job::panic_message;
+use crate::queue::Job;
+use crate::{ThreadPool, steal};
+use std::any::Any;
+use std::marker::PhantomData;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+/// 工作线程在 scope 里等待时，隔多久再找一次任务
+const HELP_INTERVAL: Duration = Duration::from_millis(1);
+
+/// 作用域，在 [`ThreadPool::scope`] 的闭包里用它提交任务
+///
+/// `'scope` 是作用域本身的生命周期，`'env` 是任务可以借用的数据的生命周期
+pub struct Scope<'scope, 'env: 'scope> {
+    pool: &'scope ThreadPool,
+    state: Arc<ScopeState>,
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'scope> Scope<'scope, '_> {
+    /// 提交一个可以借用外部数据的任务，scope 返回前它一定已经执行完
+    ///
+    /// 和 [`ThreadPool::execute`] 一样，线程池已关闭或者队列已满（Fail 策略）时 panic
+    pub fn spawn<F>(&'scope self, f: F)
+    where
+        F: FnOnce() + Send + 'scope,
+    {
+        self.state.lock().pending += 1;
+        let task = ScopeTask {
+            f,
+            guard: ScopeGuard {
+                state: Arc::clone(&self.state),
+                ran: false,
+            },
+        };
+
+        // 闭包整个捕获 task，任务没执行就被丢弃时按字段顺序先丢弃 f
+        let job = move || task.run();
+        let job: Box<dyn FnOnce() + Send + 'scope> = Box::new(job);
+        // SAFETY: scope 返回前会等到 pending 归零，也就是这个任务执行完或者被丢弃，
+        // 所以任务借用的数据在任务存在期间一直有效
+        let job = unsafe { mem::transmute::<Box<dyn FnOnce() + Send + 'scope>, Job>(job) };
+        self.pool.execute(job);
+    }
+}
+
+/// 作用域和它的任务共享的状态
+struct ScopeState {
+    tasks: Mutex<ScopeTasks>,
+    done: Condvar,
+}
+
+#[derive(Default)]
+struct ScopeTasks {
+    /// 还没结束的任务数
+    pending: usize,
+    /// 没有执行就被线程池丢弃的任务数
+    dropped: usize,
+    /// 第一个 panic 的任务的负载
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+impl ScopeState {
+    fn lock(&self) -> MutexGuard<'_, ScopeTasks> {
+        self.tasks.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// 作用域任务和它的 guard
+///
+/// 字段按声明顺序丢弃：任务没有执行就被丢弃时，先丢弃 `f`（连同它借用的数据），
+/// 再由 guard 把 pending 减一，scope 才可能返回
+struct ScopeTask<F> {
+    f: F,
+    guard: ScopeGuard,
+}
+
+impl<F: FnOnce()> ScopeTask<F> {
+    fn run(self) {
+        let ScopeTask { f, mut guard } = self;
+        guard.ran = true;
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(f)) {
+            let message = panic_message(payload.as_ref()).to_string();
+            guard.state.lock().panic.get_or_insert(payload);
+            drop(guard);
+            // 负载留给 scope 重新抛出，继续传播消息让 worker 计入 panicked_count
+            panic::resume_unwind(Box::new(message));
+        }
+    }
+}
+
+/// 任务闭包持有，任务结束或者被丢弃时把 pending 减一
+struct ScopeGuard {
+    state: Arc<ScopeState>,
+    ran: bool,
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        let mut tasks = self.state.lock();
+        if !self.ran {
+            tasks.dropped += 1;
+        }
+        tasks.pending -= 1;
+        if tasks.pending == 0 {
+            self.state.done.notify_all();
+        }
+    }
+}
+
+impl ThreadPool {
+    /// 创建一个作用域，里面提交的任务可以借用调用者栈上的数据，
+    /// 返回前等待所有任务执行完
+    ///
+    /// `f` 或者任意一个任务 panic 时，等所有任务结束后把 panic 传播给调用者；
+    /// 有任务没有执行就被丢弃（比如被 DropOldest 策略挤出队列）时也会 panic
+    ///
+    /// ```
+    /// use binfa::ThreadPool;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// let pool = ThreadPool::build(2).expect("创建线程池失败");
+    /// let numbers = vec![1, 2, 3, 4];
+    /// let sum = AtomicUsize::new(0);
+    /// pool.scope(|s| {
+    ///     for n in &numbers {
+    ///         let sum = &sum;
+    ///         s.spawn(move || {
+    ///             sum.fetch_add(*n, Ordering::SeqCst);
+    ///         });
+    ///     }
+    /// });
+    /// assert_eq!(sum.into_inner(), 10);
+    /// ```
+    pub fn scope<'env, F, T>(&self, f: F) -> T
+    where
+        F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+    {
+        let scope = Scope {
+            pool: self,
+            state: Arc::new(ScopeState {
+                tasks: Mutex::new(ScopeTasks::default()),
+                done: Condvar::new(),
+            }),
+            scope: PhantomData,
+            env: PhantomData,
+        };
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+        self.wait_scope(&scope.state);
+
+        let (panic, dropped) = {
+            let mut tasks = scope.state.lock();
+            (tasks.panic.take(), tasks.dropped)
+        };
+        let result = match result {
+            Ok(result) => result,
+            Err(payload) => panic::resume_unwind(payload),
+        };
+        if let Some(payload) = panic {
+            panic::resume_unwind(payload);
+        }
+        if dropped > 0 {
+            panic!("作用域中有 {} 个任务没有执行就被线程池丢弃", dropped);
+        }
+        result
+    }
+
+    /// 等待作用域的任务全部结束；当前线程是这个线程池的工作线程时，边等边执行任务
+    fn wait_scope(&self, state: &ScopeState) {
+        let Some((id, local)) = steal::current(&self.context.locals) else {
+            let tasks = state.lock();
+            let _tasks = state
+                .done
+                .wait_while(tasks, |tasks| tasks.pending > 0)
+                .unwrap_or_else(PoisonError::into_inner);
+            return;
+        };
+
+        loop {
+            if state.lock().pending == 0 {
+                return;
+            }
+            match self.context.find_job(id, &local) {
+                Some(job) => self.context.run_job(job),
+                None => {
+                    // 任务可能被别的线程偷走了，只等一小会儿再找
+                    let tasks = state.lock();
+                    let _tasks = state
+                        .done
+                        .wait_timeout_while(tasks, HELP_INTERVAL, |tasks| tasks.pending > 0)
+                        .unwrap_or_else(PoisonError::into_inner);
+                }
+            }
+        }
+    }
+}
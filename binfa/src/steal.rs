@@ -0,0 +1,121 @@
+/*
+在这个文件里面实现工作窃取用到的每个工作线程自己的任务队列
+LocalQueue：工作线程执行任务时再提交的任务放在这里，
+主人从队头取，其他线程没活干时从队尾偷走一半；LocalQueues 登记所有工作线程的队列
+*/
+use crate::queue::Job;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock};
+
+/// 一个工作线程自己的任务队列
+#[derive(Default)]
+pub(crate) struct LocalQueue {
+    jobs: Mutex<VecDeque<Job>>,
+}
+
+impl LocalQueue {
+    pub(crate) fn lock(&self) -> MutexGuard<'_, VecDeque<Job>> {
+        self.jobs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn push(&self, job: Job) {
+        self.lock().push_back(job);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+}
+
+/// 所有工作线程的任务队列，按 worker 编号登记
+#[derive(Default)]
+pub(crate) struct LocalQueues {
+    queues: RwLock<Vec<(usize, Arc<LocalQueue>)>>,
+}
+
+impl LocalQueues {
+    pub(crate) fn register(&self, id: usize) -> Arc<LocalQueue> {
+        let queue = Arc::new(LocalQueue::default());
+        self.queues
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((id, Arc::clone(&queue)));
+        queue
+    }
+
+    pub(crate) fn unregister(&self, id: usize) {
+        self.queues
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|(owner, _)| *owner != id);
+    }
+
+    /// 从其他线程的队列尾部偷走一半任务放进 `thief` 的队列，返回其中第一个
+    pub(crate) fn steal(&self, id: usize, thief: &LocalQueue) -> Option<Job> {
+        let queues = self.queues.read().unwrap_or_else(PoisonError::into_inner);
+        // 从自己后面的一个开始找，避免所有线程都盯着同一个队列
+        let start = queues
+            .iter()
+            .position(|(owner, _)| *owner == id)
+            .map_or(0, |index| index + 1);
+        for (owner, victim) in queues.iter().cycle().skip(start).take(queues.len()) {
+            if *owner == id {
+                continue;
+            }
+            let mut stolen = {
+                let mut jobs = victim.lock();
+                let keep = jobs.len() / 2;
+                jobs.split_off(keep)
+            };
+            if let Some(job) = stolen.pop_front() {
+                thief.lock().append(&mut stolen);
+                return Some(job);
+            }
+        }
+        None
+    }
+
+    /// 所有线程的队列里一共有多少任务
+    pub(crate) fn len(&self) -> usize {
+        let queues = self.queues.read().unwrap_or_else(PoisonError::into_inner);
+        queues.iter().map(|(_, queue)| queue.lock().len()).sum()
+    }
+
+    /// 取出所有线程队列里的任务
+    pub(crate) fn drain(&self) -> Vec<Job> {
+        let queues = self.queues.read().unwrap_or_else(PoisonError::into_inner);
+        queues
+            .iter()
+            .flat_map(|(_, queue)| std::mem::take(&mut *queue.lock()))
+            .collect()
+    }
+}
+
+thread_local! {
+    /// 当前线程是哪个线程池的工作线程（用 LocalQueues 的地址区分），以及它的编号和队列
+    static CURRENT: RefCell<Option<(usize, usize, Arc<LocalQueue>)>> = const { RefCell::new(None) };
+}
+
+/// 工作线程启动时登记自己的队列
+pub(crate) fn enter(owner: &Arc<LocalQueues>, id: usize, queue: Arc<LocalQueue>) {
+    let key = Arc::as_ptr(owner) as usize;
+    CURRENT.with(|current| *current.borrow_mut() = Some((key, id, queue)));
+}
+
+/// 工作线程退出时清除登记
+pub(crate) fn leave() {
+    CURRENT.with(|current| *current.borrow_mut() = None);
+}
+
+/// 当前线程是 `owner` 所属线程池的工作线程时，返回它的编号和队列
+pub(crate) fn current(owner: &Arc<LocalQueues>) -> Option<(usize, Arc<LocalQueue>)> {
+    let key = Arc::as_ptr(owner) as usize;
+    CURRENT.with(|current| {
+        current
+            .borrow()
+            .as_ref()
+            .filter(|(owner, _, _)| *owner == key)
+            .map(|(_, id, queue)| (*id, Arc::clone(queue)))
+    })
+}
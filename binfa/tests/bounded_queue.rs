@@ -0,0 +1,123 @@
+mod common;
+
+use binfa::{JobError, PoolCreationError, RejectionPolicy, SubmitError, ThreadPool};
+use common::block_worker;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn zero_capacity_is_rejected() {
+    assert!(matches!(
+        ThreadPool::bounded(2, 0, RejectionPolicy::Block),
+        Err(PoolCreationError::ZeroQueueCapacity)
+    ));
+}
+
+#[test]
+fn fail_policy_rejects_when_full() {
+    let pool = ThreadPool::bounded(1, 2, RejectionPolicy::Fail).unwrap();
+    let release = block_worker(&pool);
+
+    assert_eq!(pool.submit(|| {}), Ok(()));
+    assert_eq!(pool.try_execute(|| {}), Ok(()));
+    assert_eq!(pool.submit(|| {}), Err(SubmitError::QueueFull));
+    assert_eq!(pool.try_execute(|| {}), Err(SubmitError::QueueFull));
+    assert_eq!(pool.queued_count(), 2);
+    assert_eq!(pool.submitted_count(), 3);
+
+    release.send(()).unwrap();
+}
+
+#[test]
+fn block_policy_waits_for_space_but_try_execute_does_not() {
+    let pool = Arc::new(ThreadPool::bounded(1, 1, RejectionPolicy::Block).unwrap());
+    let release = block_worker(&pool);
+    pool.execute(|| {});
+    assert_eq!(pool.try_execute(|| {}), Err(SubmitError::QueueFull));
+
+    let (done, submitted) = mpsc::channel();
+    let producer = {
+        let pool = Arc::clone(&pool);
+        thread::spawn(move || {
+            pool.execute(|| {});
+            done.send(()).unwrap();
+        })
+    };
+    // 队列满着，生产者被挡住
+    assert!(submitted.recv_timeout(Duration::from_millis(50)).is_err());
+
+    release.send(()).unwrap();
+    submitted.recv_timeout(Duration::from_secs(5)).unwrap();
+    producer.join().unwrap();
+}
+
+#[test]
+fn caller_runs_policy_runs_the_job_on_the_submitting_thread() {
+    let pool = ThreadPool::bounded(1, 1, RejectionPolicy::CallerRuns).unwrap();
+    let release = block_worker(&pool);
+    pool.execute(|| {});
+
+    let ran_on = Arc::new(Mutex::new(None));
+    {
+        let ran_on = Arc::clone(&ran_on);
+        pool.try_execute(move || *ran_on.lock().unwrap() = Some(thread::current().id()))
+            .unwrap();
+    }
+    assert_eq!(*ran_on.lock().unwrap(), Some(thread::current().id()));
+    assert_eq!(pool.queued_count(), 1);
+
+    release.send(()).unwrap();
+}
+
+#[test]
+fn drop_oldest_policy_evicts_queued_jobs() {
+    let mut pool = ThreadPool::bounded(1, 2, RejectionPolicy::DropOldest).unwrap();
+    let release = block_worker(&pool);
+    let ran = Arc::new(AtomicUsize::new(0));
+    let handles: Vec<_> = (1..=4)
+        .map(|i| {
+            let ran = Arc::clone(&ran);
+            pool.execute_with_result(move || {
+                ran.fetch_add(i, Ordering::SeqCst);
+                i
+            })
+        })
+        .collect();
+    assert_eq!(pool.dropped_count(), 2);
+    assert_eq!(pool.queued_count(), 2);
+
+    release.send(()).unwrap();
+    let results: Vec<_> = handles.into_iter().map(|handle| handle.join()).collect();
+    assert!(matches!(results[0], Err(JobError::Dropped)));
+    assert!(matches!(results[1], Err(JobError::Dropped)));
+    assert_eq!(results[2].as_ref().unwrap(), &3);
+    assert_eq!(results[3].as_ref().unwrap(), &4);
+    assert_eq!(ran.load(Ordering::SeqCst), 7);
+
+    pool.shutdown();
+    assert_eq!(pool.submit(|| {}), Err(SubmitError::ShutDown));
+}
+
+#[test]
+fn shutdown_now_drops_queued_jobs() {
+    let mut pool = ThreadPool::new(1);
+    let release = block_worker(&pool);
+    let queued = pool.execute_with_result(|| ());
+
+    let closer = thread::spawn(move || {
+        pool.shutdown_now();
+        pool
+    });
+    // 正在执行的任务结束后 shutdown_now 才会返回
+    while !queued.is_finished() {
+        thread::sleep(Duration::from_millis(1));
+    }
+    release.send(()).unwrap();
+    let pool = closer.join().unwrap();
+
+    assert!(matches!(queued.join(), Err(JobError::Dropped)));
+    assert_eq!(pool.dropped_count(), 1);
+    assert_eq!(pool.completed_count(), 1);
+}
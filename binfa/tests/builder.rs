@@ -0,0 +1,155 @@
+use binfa::{MAX_THREADS, PoolCreationError, ThreadPool, ThreadPoolBuilder};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn invalid_options_are_rejected() {
+    let error = |builder: ThreadPoolBuilder| builder.build().err().unwrap();
+
+    assert!(matches!(
+        error(ThreadPool::builder().num_threads(0)),
+        PoolCreationError::ZeroSize
+    ));
+    assert!(matches!(
+        error(ThreadPool::builder().num_threads(MAX_THREADS + 1)),
+        PoolCreationError::TooLarge(n) if n == MAX_THREADS + 1
+    ));
+    assert!(matches!(
+        error(ThreadPool::builder().queue_capacity(0)),
+        PoolCreationError::ZeroQueueCapacity
+    ));
+    assert!(matches!(
+        error(ThreadPool::builder().stack_size(0)),
+        PoolCreationError::ZeroStackSize
+    ));
+    assert!(matches!(
+        error(ThreadPool::builder().thread_name("")),
+        PoolCreationError::InvalidThreadName(_)
+    ));
+    assert!(matches!(
+        error(ThreadPool::builder().thread_name("a\0b")),
+        PoolCreationError::InvalidThreadName(name) if name == "a\0b"
+    ));
+    // 栈大到操作系统无法分配
+    assert!(matches!(
+        error(
+            ThreadPool::builder()
+                .num_threads(2)
+                .stack_size(usize::MAX / 2)
+        ),
+        PoolCreationError::Spawn(_)
+    ));
+}
+
+#[test]
+fn default_thread_count_follows_available_parallelism() {
+    let expected = thread::available_parallelism().map_or(1, |n| n.get());
+    assert_eq!(ThreadPoolBuilder::new().build().unwrap().size(), expected);
+}
+
+#[test]
+fn workers_are_named_and_use_the_stack_size() {
+    let pool = ThreadPool::builder()
+        .num_threads(3)
+        .thread_name("worker")
+        .stack_size(256 * 1024)
+        .build()
+        .unwrap();
+    let mut names: Vec<_> = (0..30)
+        .map(|_| pool.execute_with_result(|| thread::current().name().map(String::from)))
+        .map(|handle| handle.join().unwrap().unwrap())
+        .collect();
+    names.sort();
+    names.dedup();
+    assert!(!names.is_empty());
+    assert!(
+        names
+            .iter()
+            .all(|name| ["worker-0", "worker-1", "worker-2"].contains(&name.as_str()))
+    );
+}
+
+#[test]
+fn start_and_exit_hooks_run_on_every_worker() {
+    let started = Arc::new(Mutex::new(Vec::new()));
+    let exited = Arc::new(AtomicUsize::new(0));
+    let mut pool = {
+        let started = Arc::clone(&started);
+        let exited = Arc::clone(&exited);
+        ThreadPool::builder()
+            .num_threads(4)
+            .on_thread_start(move |id| started.lock().unwrap().push(id))
+            .on_thread_exit(move |_| {
+                exited.fetch_add(1, Ordering::SeqCst);
+            })
+            .build()
+            .unwrap()
+    };
+    pool.execute(|| {});
+    pool.shutdown();
+
+    let mut started = started.lock().unwrap().clone();
+    started.sort();
+    assert_eq!(started, vec![0, 1, 2, 3]);
+    assert_eq!(exited.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn panic_handler_receives_payloads() {
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let mut pool = {
+        let messages = Arc::clone(&messages);
+        ThreadPool::builder()
+            .num_threads(1)
+            .panic_handler(move |payload| {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned());
+                messages.lock().unwrap().push(message);
+            })
+            .build()
+            .unwrap()
+    };
+    pool.execute(|| panic!("execute 的任务"));
+    let handle = pool.execute_with_result(|| -> () { panic!("execute_with_result 的任务") });
+    assert!(handle.join().is_err());
+    pool.shutdown();
+
+    assert_eq!(
+        *messages.lock().unwrap(),
+        vec![
+            Some("execute 的任务".to_string()),
+            Some("execute_with_result 的任务".to_string())
+        ]
+    );
+    assert_eq!(pool.panicked_count(), 2);
+}
+
+#[test]
+fn panicking_callbacks_do_not_restart_workers() {
+    let starts = Arc::new(AtomicUsize::new(0));
+    let pool = {
+        let starts = Arc::clone(&starts);
+        ThreadPool::builder()
+            .num_threads(2)
+            .on_thread_start(move |_| {
+                starts.fetch_add(1, Ordering::SeqCst);
+                panic!("启动回调出错");
+            })
+            .panic_handler(|_| panic!("panic 处理函数出错"))
+            .build()
+            .unwrap()
+    };
+
+    pool.execute(|| panic!("任务出错"));
+    assert_eq!(pool.execute_with_result(|| 6 * 7).join().unwrap(), 42);
+    thread::sleep(Duration::from_millis(50));
+
+    // 每个线程只启动了一次，没有因为回调 panic 被反复重启
+    assert_eq!(starts.load(Ordering::SeqCst), 2);
+    assert_eq!(pool.size(), 2);
+    assert_eq!(pool.panicked_count(), 1);
+}
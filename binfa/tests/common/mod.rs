@@ -0,0 +1,14 @@
+use binfa::ThreadPool;
+use std::sync::mpsc;
+
+/// 占住唯一的 worker，直到返回的发送端发出信号
+pub fn block_worker(pool: &ThreadPool) -> mpsc::Sender<()> {
+    let (release, wait) = mpsc::channel();
+    let (started, running) = mpsc::channel();
+    pool.execute(move || {
+        started.send(()).unwrap();
+        let _ = wait.recv();
+    });
+    running.recv().unwrap();
+    release
+}
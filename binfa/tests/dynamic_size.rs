@@ -0,0 +1,186 @@
+use binfa::{PoolCreationError, ThreadPool};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 轮询直到条件成立，超时则失败
+fn wait_until(what: &str, mut condition: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !condition() {
+        assert!(Instant::now() < deadline, "等待超时：{}", what);
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn elastic_pool(core: usize, max: usize, keep_alive: Duration) -> ThreadPool {
+    ThreadPool::builder()
+        .num_threads(core)
+        .max_threads(max)
+        .keep_alive(keep_alive)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn invalid_sizes_are_rejected() {
+    assert!(matches!(
+        ThreadPool::builder().num_threads(4).max_threads(2).build(),
+        Err(PoolCreationError::MaxBelowCore { core: 4, max: 2 })
+    ));
+    assert!(matches!(
+        ThreadPool::builder()
+            .num_threads(1)
+            .max_threads(1001)
+            .build(),
+        Err(PoolCreationError::TooLarge(1001))
+    ));
+
+    let pool = ThreadPool::new(2);
+    assert!(matches!(pool.resize(0), Err(PoolCreationError::ZeroSize)));
+    assert!(matches!(
+        pool.resize(1001),
+        Err(PoolCreationError::TooLarge(1001))
+    ));
+    assert_eq!(pool.size(), 2);
+}
+
+#[test]
+fn burst_grows_to_max_and_idle_threads_are_reaped() {
+    let pool = elastic_pool(1, 4, Duration::from_millis(50));
+    assert_eq!(pool.size(), 1);
+
+    // 4 个任务必须同时在跑才能通过栅栏，说明线程确实加到了 4 个
+    let barrier = Arc::new(Barrier::new(4));
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let barrier = Arc::clone(&barrier);
+            pool.execute_with_result(move || {
+                barrier.wait();
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join_timeout(Duration::from_secs(5)).unwrap();
+    }
+    assert_eq!(pool.size(), 4);
+
+    // 空闲超过 keep-alive 后回到核心线程数
+    wait_until("回收空闲线程", || pool.size() == 1);
+    thread::sleep(Duration::from_millis(150));
+    assert_eq!(pool.size(), 1);
+}
+
+#[test]
+fn fixed_pool_never_grows() {
+    let pool = ThreadPool::new(2);
+    let handles: Vec<_> = (0..20)
+        .map(|_| pool.execute_with_result(|| thread::sleep(Duration::from_millis(2))))
+        .collect();
+    assert_eq!(pool.size(), 2);
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(pool.max_size(), 2);
+}
+
+#[test]
+fn resize_grows_immediately_and_shrinks_idle_threads() {
+    let pool = ThreadPool::new(2);
+
+    pool.resize(5).unwrap();
+    assert_eq!(pool.size(), 5);
+    assert_eq!(pool.core_size(), 5);
+    assert_eq!(pool.max_size(), 5);
+
+    pool.resize(1).unwrap();
+    wait_until("缩容", || pool.size() == 1);
+    assert_eq!(pool.core_size(), 1);
+    // 缩容后最大线程数不变，仍然可以应付突发负载
+    assert_eq!(pool.max_size(), 5);
+    assert_eq!(pool.execute_with_result(|| 7).join().unwrap(), 7);
+}
+
+#[test]
+fn busy_threads_exit_after_their_job_when_shrinking() {
+    let pool = ThreadPool::new(3);
+    let (release, wait) = mpsc::channel::<()>();
+    let wait = Arc::new(std::sync::Mutex::new(wait));
+    let (started, running) = mpsc::channel();
+    let handles: Vec<_> = (0..3)
+        .map(|_| {
+            let wait = Arc::clone(&wait);
+            let started = started.clone();
+            pool.execute_with_result(move || {
+                started.send(()).unwrap();
+                let _ = wait.lock().unwrap().recv();
+            })
+        })
+        .collect();
+    for _ in 0..3 {
+        running.recv().unwrap();
+    }
+
+    pool.resize(1).unwrap();
+    // 三个线程都在忙，暂时都不退出
+    thread::sleep(Duration::from_millis(30));
+    assert_eq!(pool.size(), 3);
+
+    for _ in 0..3 {
+        release.send(()).unwrap();
+    }
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    wait_until("忙碌线程做完任务后退出", || pool.size() == 1);
+}
+
+#[test]
+fn resize_back_up_cancels_pending_shrink() {
+    let pool = ThreadPool::new(2);
+    let (release, wait) = mpsc::channel::<()>();
+    let blocker = pool.execute_with_result(move || {
+        let _ = wait.recv();
+    });
+    wait_until("任务开始", || pool.active_count() == 1);
+
+    // 忙碌的线程还没来得及退出就又扩容回来
+    pool.resize(1).unwrap();
+    pool.resize(2).unwrap();
+    release.send(()).unwrap();
+    blocker.join().unwrap();
+
+    thread::sleep(Duration::from_millis(30));
+    assert_eq!(pool.size(), 2);
+}
+
+#[test]
+fn bursty_load_completes_every_job_within_limits() {
+    let mut pool = elastic_pool(2, 6, Duration::from_millis(20));
+    let done = Arc::new(AtomicUsize::new(0));
+    let mut peak = 0;
+
+    for burst in 0..5 {
+        for _ in 0..40 {
+            let done = Arc::clone(&done);
+            pool.execute(move || {
+                thread::sleep(Duration::from_millis(1));
+                done.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        let size = pool.size();
+        assert!((2..=6).contains(&size), "第 {} 轮线程数 {}", burst, size);
+        peak = peak.max(size);
+
+        // 两轮之间的间隔比 keep-alive 长，多出来的线程会被回收
+        wait_until("这一轮任务完成", || {
+            done.load(Ordering::SeqCst) == (burst + 1) * 40
+        });
+        wait_until("回到核心线程数", || pool.size() == 2);
+    }
+
+    assert!(peak > 2, "突发负载下应该临时增加线程");
+    pool.shutdown();
+    assert_eq!(pool.size(), 0);
+    assert_eq!(pool.completed_count(), 200);
+}
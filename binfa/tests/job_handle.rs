@@ -0,0 +1,78 @@
+mod common;
+
+use binfa::{JobError, ThreadPool};
+use common::block_worker;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::time::Duration;
+
+#[test]
+fn try_join_and_join_timeout_wait_for_the_result() {
+    let pool = ThreadPool::new(1);
+    let release = block_worker(&pool);
+    let handle = pool.execute_with_result(|| "完成");
+
+    assert!(!handle.is_finished());
+    assert!(matches!(handle.try_join(), Err(JobError::NotFinished)));
+    assert!(matches!(
+        handle.join_timeout(Duration::from_millis(20)),
+        Err(JobError::Timeout)
+    ));
+
+    release.send(()).unwrap();
+    assert_eq!(handle.join_timeout(Duration::from_secs(5)).unwrap(), "完成");
+    assert!(handle.is_finished());
+    // 结果只能取一次
+    assert!(matches!(handle.try_join(), Err(JobError::AlreadyJoined)));
+}
+
+#[test]
+fn cancel_skips_a_queued_job() {
+    let pool = ThreadPool::new(1);
+    let release = block_worker(&pool);
+    let ran = Arc::new(AtomicBool::new(false));
+    let handle = {
+        let ran = Arc::clone(&ran);
+        pool.execute_with_result(move || ran.store(true, Ordering::SeqCst))
+    };
+
+    assert!(handle.cancel());
+    assert!(handle.is_finished());
+    // 已经取消的任务不能再取消
+    assert!(!handle.cancel());
+
+    release.send(()).unwrap();
+    let after = pool.execute_with_result(|| ());
+    after.join().unwrap();
+    assert!(!ran.load(Ordering::SeqCst));
+    assert!(matches!(handle.join(), Err(JobError::Cancelled)));
+}
+
+#[test]
+fn running_job_cannot_be_cancelled() {
+    let pool = ThreadPool::new(1);
+    let (started, running) = mpsc::channel();
+    let (release, wait) = mpsc::channel::<()>();
+    let handle = pool.execute_with_result(move || {
+        started.send(()).unwrap();
+        let _ = wait.recv();
+        7
+    });
+
+    running.recv().unwrap();
+    assert!(!handle.cancel());
+    release.send(()).unwrap();
+    assert_eq!(handle.join().unwrap(), 7);
+}
+
+#[test]
+fn panicked_job_keeps_its_payload() {
+    let pool = ThreadPool::new(1);
+    let error = pool
+        .execute_with_result(|| -> u8 { panic!("坏了") })
+        .join()
+        .unwrap_err();
+    assert_eq!(error.to_string(), "任务执行时 panic：坏了");
+    assert!(std::error::Error::source(&error).is_some());
+    assert!(matches!(error, JobError::Panicked(_)));
+}
@@ -0,0 +1,134 @@
+use binfa::ThreadPool;
+use std::cmp::Ordering as CmpOrdering;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// 简单的伪随机数，测试数据不依赖外部 crate
+fn pseudo_random(len: usize) -> Vec<u32> {
+    let mut state = 0x2545_f491_u32;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state % 1000
+        })
+        .collect()
+}
+
+#[test]
+fn map_preserves_input_order() {
+    let pool = ThreadPool::new(4);
+    // 靠前的元素睡得更久，先完成的是后面的块
+    let result = pool.map(0..40u64, |n| {
+        thread::sleep(Duration::from_millis(40 - n));
+        n * 10
+    });
+    assert_eq!(result, (0..40).map(|n| n * 10).collect::<Vec<_>>());
+    assert!(pool.map(Vec::<u8>::new(), |n| n).is_empty());
+}
+
+#[test]
+fn map_can_borrow_local_data() {
+    let pool = ThreadPool::new(3);
+    let names = vec!["甲".to_string(), "乙".to_string(), "丙".to_string()];
+    let suffix = String::from("号");
+    let labels = pool.map(&names, |name| format!("{}{}", name, suffix));
+    assert_eq!(labels, ["甲号", "乙号", "丙号"]);
+}
+
+#[test]
+fn for_each_visits_every_item_once() {
+    let pool = ThreadPool::new(4);
+    let seen = Mutex::new(Vec::new());
+    let count = AtomicUsize::new(0);
+    pool.for_each(0..1000, |n| {
+        count.fetch_add(1, Ordering::SeqCst);
+        seen.lock().unwrap().push(n);
+    });
+
+    let mut seen = seen.into_inner().unwrap();
+    seen.sort();
+    assert_eq!(count.into_inner(), 1000);
+    assert_eq!(seen, (0..1000).collect::<Vec<_>>());
+}
+
+#[test]
+fn map_reduce_combines_in_input_order() {
+    let pool = ThreadPool::new(4);
+    let sum = pool.map_reduce(1..=1000u64, |n| n * n, |a, b| a + b);
+    assert_eq!(sum, Some((1..=1000u64).map(|n| n * n).sum()));
+
+    // 字符串拼接不满足交换律，结果仍然按输入顺序
+    let text = pool.map_reduce(0..200, |n| n.to_string(), |a, b| a + &b);
+    assert_eq!(text, Some((0..200).map(|n| n.to_string()).collect()));
+
+    assert_eq!(pool.map_reduce(0..0, |n| n, |a, b| a + b), None);
+}
+
+#[test]
+fn par_sort_matches_std_sort() {
+    let pool = ThreadPool::new(4);
+    for len in [0, 1, 2, 15, 16, 17, 1000, 12345] {
+        let mut data = pseudo_random(len);
+        let mut expected = data.clone();
+        expected.sort();
+        pool.par_sort(&mut data);
+        assert_eq!(data, expected, "长度 {}", len);
+    }
+}
+
+/// 只按 key 比较的元素，用来检查排序是否稳定
+#[derive(Debug, PartialEq, Eq)]
+struct Keyed {
+    key: u32,
+    index: usize,
+}
+
+impl PartialOrd for Keyed {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Keyed {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        self.key.cmp(&other.key)
+    }
+}
+
+#[test]
+fn par_sort_is_stable() {
+    let pool = ThreadPool::new(3);
+    let mut data: Vec<Keyed> = pseudo_random(5000)
+        .into_iter()
+        .enumerate()
+        .map(|(index, n)| Keyed { key: n % 10, index })
+        .collect();
+    pool.par_sort(&mut data);
+
+    for pair in data.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        assert!(a.key < b.key || (a.key == b.key && a.index < b.index));
+    }
+}
+
+#[test]
+fn panics_in_helpers_reach_the_caller() {
+    let pool = ThreadPool::new(2);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        pool.map(0..10, |n| {
+            if n == 7 {
+                panic!("第 7 个元素出错");
+            }
+            n
+        })
+    }));
+    assert!(result.is_err());
+
+    // 线程池照常可用
+    assert_eq!(pool.map(0..3, |n| n + 1), vec![1, 2, 3]);
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use binfa::{Priority, RejectionPolicy, ThreadPool};
+use common::block_worker;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// 只有一个 worker 的线程池，任务每排队 `aging` 提升一级优先级
+fn single_worker(aging: Duration) -> ThreadPool {
+    ThreadPool::builder()
+        .num_threads(1)
+        .priority_aging(aging)
+        .build()
+        .unwrap()
+}
+
+/// 提交一个按执行顺序记下名字的任务
+fn record(
+    pool: &ThreadPool,
+    order: &Arc<Mutex<Vec<&'static str>>>,
+    priority: Priority,
+    name: &'static str,
+) {
+    let order = Arc::clone(order);
+    pool.execute_with_priority(priority, move || order.lock().unwrap().push(name));
+}
+
+#[test]
+fn higher_priority_jobs_run_first() {
+    let mut pool = single_worker(Duration::from_secs(60));
+    let release = block_worker(&pool);
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    record(&pool, &order, Priority::Low, "低1");
+    record(&pool, &order, Priority::Normal, "普通1");
+    record(&pool, &order, Priority::High, "高1");
+    record(&pool, &order, Priority::Low, "低2");
+    record(&pool, &order, Priority::High, "高2");
+    record(&pool, &order, Priority::Normal, "普通2");
+
+    release.send(()).unwrap();
+    pool.shutdown();
+    assert_eq!(
+        *order.lock().unwrap(),
+        ["高1", "高2", "普通1", "普通2", "低1", "低2"]
+    );
+}
+
+#[test]
+fn queued_count_is_broken_down_by_priority() {
+    let pool = single_worker(Duration::from_secs(60));
+    let release = block_worker(&pool);
+
+    for _ in 0..3 {
+        pool.execute_with_priority(Priority::High, || {});
+    }
+    pool.execute(|| {});
+    for _ in 0..2 {
+        pool.execute_with_priority(Priority::Low, || {});
+    }
+
+    assert_eq!(pool.queued_count_by_priority(Priority::High), 3);
+    assert_eq!(pool.queued_count_by_priority(Priority::Normal), 1);
+    assert_eq!(pool.queued_count_by_priority(Priority::Low), 2);
+    assert_eq!(pool.queued_count(), 6);
+    let total: usize = Priority::ALL
+        .iter()
+        .map(|priority| pool.queued_count_by_priority(*priority))
+        .sum();
+    assert_eq!(total, pool.queued_count());
+
+    release.send(()).unwrap();
+}
+
+#[test]
+fn aging_keeps_low_priority_jobs_from_starving() {
+    let mut pool = single_worker(Duration::from_millis(30));
+    let release = block_worker(&pool);
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    record(&pool, &order, Priority::Low, "低");
+    // 等过两个提升间隔，低优先级的任务已经提升到最高一级，而且来得最早
+    thread::sleep(Duration::from_millis(100));
+    record(&pool, &order, Priority::High, "高1");
+    record(&pool, &order, Priority::High, "高2");
+
+    release.send(()).unwrap();
+    pool.shutdown();
+    assert_eq!(*order.lock().unwrap(), ["低", "高1", "高2"]);
+}
+
+#[test]
+fn drop_oldest_evicts_the_lowest_priority_job() {
+    let mut pool = ThreadPool::builder()
+        .num_threads(1)
+        .queue_capacity(2)
+        .rejection_policy(RejectionPolicy::DropOldest)
+        .priority_aging(Duration::from_secs(60))
+        .build()
+        .unwrap();
+    let release = block_worker(&pool);
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    record(&pool, &order, Priority::High, "高");
+    record(&pool, &order, Priority::Low, "低");
+    record(&pool, &order, Priority::Normal, "普通");
+
+    release.send(()).unwrap();
+    pool.shutdown();
+    assert_eq!(*order.lock().unwrap(), ["高", "普通"]);
+    assert_eq!(pool.dropped_count(), 1);
+}
+
+#[test]
+fn high_priority_jobs_overtake_a_workers_own_queue() {
+    let pool = Arc::new(single_worker(Duration::from_secs(60)));
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    // 父任务提交的普通任务在 worker 自己的队列里，高优先级任务仍然先执行
+    let parent = {
+        let (pool, order) = (Arc::clone(&pool), Arc::clone(&order));
+        pool.clone().execute_with_result(move || {
+            record(&pool, &order, Priority::Normal, "普通1");
+            record(&pool, &order, Priority::Normal, "普通2");
+            record(&pool, &order, Priority::High, "高");
+        })
+    };
+    parent.join().unwrap();
+
+    while pool.completed_count() < 4 {
+        thread::yield_now();
+    }
+    assert_eq!(*order.lock().unwrap(), ["高", "普通1", "普通2"]);
+}
+
+/// 不停地在 worker 自己的队列里重新提交自己，直到低优先级任务执行过或者轮数用完
+fn resubmit(pool: Arc<ThreadPool>, low_ran: Arc<AtomicBool>, rounds: Arc<AtomicUsize>) {
+    if low_ran.load(Ordering::SeqCst) || rounds.fetch_add(1, Ordering::SeqCst) >= 1000 {
+        return;
+    }
+    thread::sleep(Duration::from_millis(1));
+    let next = Arc::clone(&pool);
+    pool.execute(move || resubmit(next, low_ran, rounds));
+}
+
+#[test]
+fn aging_reaches_jobs_behind_a_busy_local_queue() {
+    let pool = Arc::new(single_worker(Duration::from_millis(20)));
+    let low_ran = Arc::new(AtomicBool::new(false));
+    let rounds = Arc::new(AtomicUsize::new(0));
+
+    {
+        let (next, low_ran, rounds) =
+            (Arc::clone(&pool), Arc::clone(&low_ran), Arc::clone(&rounds));
+        pool.execute(move || resubmit(next, low_ran, rounds));
+    }
+    while rounds.load(Ordering::SeqCst) < 5 {
+        thread::yield_now();
+    }
+    {
+        let low_ran = Arc::clone(&low_ran);
+        pool.execute_with_priority(Priority::Low, move || low_ran.store(true, Ordering::SeqCst));
+    }
+
+    // 等重新提交的任务停下来，线程池只剩这里一个引用
+    while Arc::strong_count(&pool) > 1 {
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert!(low_ran.load(Ordering::SeqCst));
+    assert!(rounds.load(Ordering::SeqCst) < 1000);
+}
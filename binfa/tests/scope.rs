@@ -0,0 +1,191 @@
+use binfa::{RejectionPolicy, ThreadPool};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn jobs_can_borrow_and_mutate_local_data() {
+    let pool = ThreadPool::new(3);
+    let mut numbers: Vec<u64> = (1..=100).collect();
+    let offset = 1000;
+
+    let chunks = pool.scope(|s| {
+        let mut chunks = 0;
+        for chunk in numbers.chunks_mut(7) {
+            s.spawn(|| {
+                for n in chunk {
+                    *n += offset;
+                }
+            });
+            chunks += 1;
+        }
+        chunks
+    });
+
+    assert_eq!(chunks, 15);
+    assert_eq!(numbers, (1001..=1100).collect::<Vec<u64>>());
+}
+
+#[test]
+fn scope_waits_for_every_job() {
+    let pool = ThreadPool::new(2);
+    let finished = AtomicUsize::new(0);
+
+    pool.scope(|s| {
+        for i in 0..6 {
+            let finished = &finished;
+            s.spawn(move || {
+                thread::sleep(Duration::from_millis(10 * i));
+                finished.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+    });
+
+    assert_eq!(finished.load(Ordering::SeqCst), 6);
+    assert_eq!(pool.completed_count(), 6);
+}
+
+#[test]
+fn jobs_can_spawn_more_jobs_into_the_same_scope() {
+    let pool = ThreadPool::new(2);
+    let count = AtomicUsize::new(0);
+
+    pool.scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for _ in 0..4 {
+                    s.spawn(|| {
+                        count.fetch_add(1, Ordering::SeqCst);
+                    });
+                }
+            });
+        }
+    });
+
+    assert_eq!(count.into_inner(), 16);
+}
+
+#[test]
+fn scope_inside_a_job_does_not_deadlock_a_single_worker() {
+    let pool = ThreadPool::new(1);
+
+    // 唯一的 worker 在 scope 里等待时会自己执行子任务
+    let total = AtomicUsize::new(0);
+    pool.scope(|outer| {
+        outer.spawn(|| {
+            let parts = [1, 2, 3];
+            pool.scope(|inner| {
+                for part in &parts {
+                    let total = &total;
+                    inner.spawn(move || {
+                        total.fetch_add(*part, Ordering::SeqCst);
+                    });
+                }
+            });
+        });
+    });
+
+    assert_eq!(total.into_inner(), 6);
+}
+
+#[test]
+fn job_panic_is_propagated_after_the_other_jobs_finish() {
+    let pool = ThreadPool::new(2);
+    let finished = AtomicUsize::new(0);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        pool.scope(|s| {
+            s.spawn(|| panic!("作用域里的任务出错了"));
+            for _ in 0..3 {
+                s.spawn(|| {
+                    thread::sleep(Duration::from_millis(20));
+                    finished.fetch_add(1, Ordering::SeqCst);
+                });
+            }
+        })
+    }));
+
+    let payload = result.unwrap_err();
+    assert_eq!(
+        payload.downcast_ref::<&str>(),
+        Some(&"作用域里的任务出错了")
+    );
+    assert_eq!(finished.load(Ordering::SeqCst), 3);
+
+    // 任务 panic 照常计数，worker 继续可用
+    assert_eq!(pool.panicked_count(), 1);
+    assert_eq!(pool.scope(|_| 42), 42);
+}
+
+#[test]
+fn dropped_jobs_make_the_scope_panic() {
+    let pool = ThreadPool::bounded(1, 1, RejectionPolicy::DropOldest).unwrap();
+    let (started, running) = mpsc::channel();
+    let (release, wait) = mpsc::channel::<()>();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        pool.scope(|s| {
+            s.spawn(move || {
+                started.send(()).unwrap();
+                let _ = wait.recv();
+            });
+            running.recv().unwrap();
+            // 第二个任务被第三个挤出队列
+            s.spawn(|| {});
+            s.spawn(|| {});
+            release.send(()).unwrap();
+        })
+    }));
+
+    let payload = result.unwrap_err();
+    let message = payload.downcast_ref::<String>().unwrap();
+    assert!(message.contains("1 个任务"), "{}", message);
+    assert_eq!(pool.dropped_count(), 1);
+}
+
+/// 丢弃时过一会儿再写借用的数据
+struct SlowDrop<'a>(&'a AtomicBool);
+
+impl Drop for SlowDrop<'_> {
+    fn drop(&mut self) {
+        thread::sleep(Duration::from_millis(50));
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn scope_outlives_the_drop_of_a_discarded_job() {
+    let pool = ThreadPool::bounded(1, 1, RejectionPolicy::DropOldest).unwrap();
+    let (started, running) = mpsc::channel();
+    let (release, wait) = mpsc::channel::<()>();
+    pool.execute(move || {
+        started.send(()).unwrap();
+        let _ = wait.recv();
+    });
+    running.recv().unwrap();
+
+    let dropped = AtomicBool::new(false);
+    thread::scope(|threads| {
+        let waiter = threads.spawn(|| {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                pool.scope(|s| {
+                    let slow = SlowDrop(&dropped);
+                    s.spawn(move || drop(slow));
+                })
+            }));
+            // scope 返回时，被丢弃的任务连同它借用的数据必须已经丢弃完
+            (result.is_err(), dropped.load(Ordering::SeqCst))
+        });
+
+        while pool.queued_count() == 0 {
+            thread::yield_now();
+        }
+        // 另一个线程挤掉作用域里的任务，在这个线程上丢弃它
+        pool.try_execute(|| {}).unwrap();
+        assert_eq!(waiter.join().unwrap(), (true, true));
+    });
+
+    release.send(()).unwrap();
+}
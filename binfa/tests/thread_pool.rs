@@ -0,0 +1,176 @@
+use binfa::{JobError, PoolCreationError, ThreadPool};
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn build_rejects_invalid_sizes() {
+    assert!(matches!(
+        ThreadPool::build(0),
+        Err(PoolCreationError::ZeroSize)
+    ));
+    assert!(matches!(
+        ThreadPool::build(1001),
+        Err(PoolCreationError::TooLarge(1001))
+    ));
+    assert_eq!(
+        PoolCreationError::TooLarge(2000).to_string(),
+        "线程池大小 2000 超过最大限制 1000"
+    );
+    assert_eq!(ThreadPool::build(1000).unwrap().size(), 1000);
+}
+
+#[test]
+fn drop_waits_for_all_jobs() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    {
+        let pool = ThreadPool::new(4);
+        for _ in 0..20 {
+            let counter = Arc::clone(&counter);
+            pool.execute(move || {
+                thread::sleep(Duration::from_millis(5));
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+    }
+    assert_eq!(counter.load(Ordering::SeqCst), 20);
+}
+
+#[test]
+fn execute_with_result_returns_values() {
+    let pool = ThreadPool::new(3);
+    let handles: Vec<_> = (0..10)
+        .map(|i| pool.execute_with_result(move || i * i))
+        .collect();
+    let results: Vec<_> = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+    assert_eq!(results, (0..10).map(|i| i * i).collect::<Vec<_>>());
+}
+
+#[test]
+fn jobs_run_on_all_workers_concurrently() {
+    // 每个任务都要等其余任务到齐才能结束，只有真正并行执行时才不会卡住
+    let pool = ThreadPool::new(4);
+    let barrier = Arc::new(Barrier::new(4));
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let barrier = Arc::clone(&barrier);
+            pool.execute_with_result(move || barrier.wait().is_leader())
+        })
+        .collect();
+    let leaders = handles
+        .iter()
+        .filter(|handle| handle.join_timeout(Duration::from_secs(5)).unwrap())
+        .count();
+    assert_eq!(leaders, 1);
+}
+
+#[test]
+fn counters_track_submitted_active_and_queued_jobs() {
+    let mut pool = ThreadPool::new(2);
+    let gate = Arc::new(Barrier::new(3));
+    for _ in 0..2 {
+        let gate = Arc::clone(&gate);
+        pool.execute(move || {
+            gate.wait();
+        });
+    }
+    pool.execute(|| {});
+
+    // 等两个 worker 都领走了任务
+    while pool.active_count() < 2 {
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(pool.submitted_count(), 3);
+    assert_eq!(pool.queued_count(), 1);
+    assert_eq!(pool.completed_count(), 0);
+
+    gate.wait();
+    pool.shutdown();
+    assert_eq!(pool.active_count(), 0);
+    assert_eq!(pool.queued_count(), 0);
+    assert_eq!(pool.completed_count(), 3);
+}
+
+#[test]
+fn shutdown_now_joins_all_workers() {
+    let mut pool = ThreadPool::new(1);
+    let started = Arc::new(AtomicUsize::new(0));
+    for _ in 0..3 {
+        let started = Arc::clone(&started);
+        pool.execute(move || {
+            started.fetch_add(1, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(50));
+        });
+    }
+    pool.shutdown_now();
+    // 所有 worker 都已退出，不会再有任务在执行
+    assert_eq!(pool.active_count(), 0);
+    assert_eq!(pool.completed_count(), started.load(Ordering::SeqCst));
+    // 之后 drop 不会再次关闭
+    drop(pool);
+}
+
+#[test]
+fn panicking_job_is_reported_and_worker_survives() {
+    let mut pool = ThreadPool::new(1);
+    let handle = pool.execute_with_result(|| -> i32 { panic!("第 {} 个任务出错", 1) });
+    let Err(JobError::Panicked(error)) = handle.join() else {
+        panic!("任务应该 panic");
+    };
+    assert_eq!(error.message(), "第 1 个任务出错");
+    assert_eq!(error.to_string(), "任务执行时 panic：第 1 个任务出错");
+
+    pool.execute(|| panic!("没有人接收结果"));
+    // 唯一的 worker 仍然能执行后面的任务
+    assert_eq!(pool.execute_with_result(|| 42).join().unwrap(), 42);
+
+    pool.shutdown();
+    assert_eq!(pool.panicked_count(), 2);
+    assert_eq!(pool.completed_count(), 1);
+    assert_eq!(pool.active_count(), 0);
+    assert_eq!(pool.queued_count(), 0);
+}
+
+#[test]
+fn panic_payload_is_returned_to_the_caller() {
+    let pool = ThreadPool::new(2);
+    let handle = pool.execute_with_result(|| -> () { panic::panic_any(7_u8) });
+    let Err(JobError::Panicked(error)) = handle.join() else {
+        panic!("任务应该 panic");
+    };
+    assert_eq!(error.message(), "未知的 panic");
+    assert_eq!(error.into_payload().downcast_ref::<u8>(), Some(&7));
+}
+
+/// drop 时 panic 的负载：worker 捕获任务的 panic 之后丢弃负载，会在 catch_unwind 之外再次 panic
+struct PanicOnDrop;
+
+impl Drop for PanicOnDrop {
+    fn drop(&mut self) {
+        panic!("丢弃负载时 panic");
+    }
+}
+
+#[test]
+fn dead_worker_is_respawned() {
+    let mut pool = ThreadPool::new(1);
+    pool.execute(|| panic::panic_any(PanicOnDrop));
+
+    // 线程退出后会被重新启动，队列中的任务不会卡住
+    let handle = pool.execute_with_result(|| "仍然可用");
+    assert_eq!(
+        handle.join_timeout(Duration::from_secs(5)).unwrap(),
+        "仍然可用"
+    );
+    assert_eq!(pool.size(), 1);
+
+    // 关闭时不会因为线程异常退出而 panic
+    pool.shutdown();
+    assert_eq!(pool.panicked_count(), 1);
+    assert_eq!(pool.completed_count(), 1);
+}
@@ -0,0 +1,107 @@
+use binfa::{JobError, JobHandle, ThreadPool};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier, mpsc};
+use std::time::Duration;
+
+#[test]
+fn jobs_submitted_by_a_worker_are_stolen_by_idle_workers() {
+    let pool = Arc::new(ThreadPool::new(4));
+    let barrier = Arc::new(Barrier::new(4));
+
+    // 子任务都在父任务所在线程的队列里，只有被另外 3 个线程偷走才能同时通过栅栏
+    let parent = {
+        let pool = Arc::clone(&pool);
+        pool.clone().execute_with_result(move || {
+            (0..4)
+                .map(|_| {
+                    let barrier = Arc::clone(&barrier);
+                    pool.execute_with_result(move || barrier.wait().is_leader())
+                })
+                .collect::<Vec<JobHandle<bool>>>()
+        })
+    };
+
+    let leaders = parent
+        .join()
+        .unwrap()
+        .iter()
+        .filter(|child| child.join_timeout(Duration::from_secs(5)).unwrap())
+        .count();
+    assert_eq!(leaders, 1);
+}
+
+#[test]
+fn nested_jobs_count_as_queued_until_they_run() {
+    let pool = Arc::new(ThreadPool::new(1));
+    let (release, wait) = mpsc::channel::<()>();
+    let queued = {
+        let pool = Arc::clone(&pool);
+        pool.clone().execute_with_result(move || {
+            for _ in 0..5 {
+                pool.execute(|| {});
+            }
+            let queued = pool.queued_count();
+            let _ = wait.recv();
+            queued
+        })
+    };
+
+    release.send(()).unwrap();
+    assert_eq!(queued.join().unwrap(), 5);
+    while pool.completed_count() < 6 {
+        std::thread::yield_now();
+    }
+    assert_eq!(pool.queued_count(), 0);
+    assert_eq!(pool.submitted_count(), 6);
+}
+
+#[test]
+fn many_tiny_jobs_all_run_exactly_once() {
+    let mut pool = ThreadPool::new(4);
+    let sum = Arc::new(AtomicUsize::new(0));
+    for i in 0..20_000 {
+        let sum = Arc::clone(&sum);
+        pool.execute(move || {
+            sum.fetch_add(i, Ordering::Relaxed);
+        });
+    }
+    pool.shutdown();
+
+    assert_eq!(sum.load(Ordering::Relaxed), (0..20_000).sum::<usize>());
+    assert_eq!(pool.completed_count(), 20_000);
+    assert_eq!(pool.queued_count(), 0);
+}
+
+#[test]
+fn shutdown_now_drops_jobs_waiting_in_worker_queues() {
+    let pool = Arc::new(ThreadPool::new(1));
+    let (ready, children) = mpsc::channel();
+
+    // 父任务占住唯一的线程，子任务都留在它自己的队列里；
+    // 子任务被丢弃时 sender 也跟着释放，父任务这才结束
+    {
+        let pool = Arc::clone(&pool);
+        pool.clone().execute(move || {
+            let (alive, dropped) = mpsc::channel::<()>();
+            let handles: Vec<_> = (0..3)
+                .map(|_| {
+                    let alive = alive.clone();
+                    pool.execute_with_result(move || drop(alive))
+                })
+                .collect();
+            drop((alive, pool));
+            ready.send(handles).unwrap();
+            while dropped.recv().is_ok() {}
+        });
+    }
+
+    let handles = children.recv().unwrap();
+    assert_eq!(pool.queued_count(), 3);
+
+    let mut pool = Arc::try_unwrap(pool).ok().expect("父任务已经释放了线程池");
+    pool.shutdown_now();
+    for handle in handles {
+        assert!(matches!(handle.join(), Err(JobError::Dropped)));
+    }
+    assert_eq!(pool.dropped_count(), 3);
+}
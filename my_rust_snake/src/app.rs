@@ -1,85 +1,301 @@
-use crate::game::game_direction::{Direction, InputDevice};
-use crate::game::{self, game_display, snake};
-use crossterm::{
-    cursor::MoveTo,
-    execute,
-    terminal::{Clear, ClearType},
+use crate::cli::{ExportFormat, InputKind, Options, RendererKind};
+use my_rust_snake::game::daily::{self, RESULTS_FILE};
+use my_rust_snake::game::telemetry;
+use my_rust_snake::{
+    Achievements, AnsiRenderer, BorderFlash, Canvas, ChallengeDate, CrosstermRenderer, DailyResult,
+    Direction, EventBus, EventLogger, GameError, GameEvent, GameMode, GameState, InputDevice,
+    KeyboardInput, MemoryRenderer, MouseInput, Recording, Renderer, ScriptInput, Snake,
+    TICK_MILLIS, TelemetryLogger, TerminalFeedback, TickSample, spawn_snake, steer, step,
 };
-use std::io::stdout;
-use std::{thread, time::Duration};
-pub fn run() {
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use std::{fs, thread};
+pub fn run(options: &Options) -> Result<(), GameError> {
+    let mode = options.mode;
+    //每日挑战的种子由日期决定；录像必须知道种子，没有指定时随机选一个
+    let seed = mode
+        .daily_seed()
+        .or(options.seed)
+        .or_else(|| options.record.as_ref().map(|_| rand::random()));
+    let headless = options.headless;
+    //渲染后端在重新开始时保持不变；无界面模式下画面只留在内存里
+    let mut renderer: Box<dyn Renderer> = match options.renderer {
+        _ if headless => Box::new(MemoryRenderer::new()),
+        RendererKind::Terminal => Box::new(CrosstermRenderer),
+        RendererKind::Ansi => Box::new(AnsiRenderer::stdout()),
+    };
+    //无界面模式不需要等待，直接全速推进
+    let tick_duration = if headless {
+        Duration::ZERO
+    } else {
+        Duration::from_millis(TICK_MILLIS)
+    };
+    //事件订阅者在重新开始时保持不变；无界面模式不需要终端反馈
+    let border_flash = BorderFlash::default();
+    let mut event_bus = EventBus::new();
+    if let Some(style) = options.feedback
+        && !headless
+    {
+        event_bus.subscribe(Box::new(TerminalFeedback::new(style, border_flash.clone())));
+    }
+    if let Some(path) = &options.event_log {
+        event_bus.subscribe(Box::new(EventLogger::open(path)?));
+    }
+    //成就只在有人玩的时候统计，脚本跑出来的对局不算
+    let achievements = (!headless).then(|| Achievements::load(Achievements::FILE));
+    if let Some(achievements) = &achievements {
+        event_bus.subscribe(Box::new(achievements.clone()));
+    }
     //初始化封装成闭包或内部函数，方便重置
+    let show_mouse_buttons = options.input == InputKind::Mouse;
+    let half_block = options.half_block;
     let setup = || {
+        let mut canvas = if half_block {
+            Canvas::with_half_block()
+        } else {
+            Canvas::new()
+        };
+        canvas.set_show_mouse_buttons(show_mouse_buttons);
+        canvas.set_border_flash(border_flash.clone());
+        if let Some(achievements) = &achievements {
+            achievements.set_board_cells(canvas.get_canvas_width() * canvas.get_canvas_height());
+            canvas.set_achievements(achievements.clone());
+        }
+        //指定了种子时，每次（包括重新开始）都从同一个种子生成食物
         (
-            game::game_direction::KeyboardInput,
-            game::GameState::new(),
-            snake::Snake::new(),
-            game_display::Canvas::new(),
+            GameState::new(mode),
+            spawn_snake(&canvas, mode, seed),
+            canvas,
         )
     };
 
-    let (mut input_device, mut game_state, mut snake, mut canvas) = setup();
+    let (mut game_state, mut snake, mut canvas) = setup();
+    //遥测日志在重新开始时保持不变，每局开始时写一行 start
+    let mut telemetry = match &options.telemetry {
+        Some(path) => Some(TelemetryLogger::open(path)?),
+        None => None,
+    };
+    if let Some(telemetry) = telemetry.as_mut() {
+        telemetry.start_game(mode, canvas.get_canvas_width(), canvas.get_canvas_height())?;
+    }
+    let mut last_tick = Instant::now();
+    //输入设备在重新开始时保持不变
+    let mut input_device: Box<dyn InputDevice> = match &options.input {
+        InputKind::Keyboard => Box::new(KeyboardInput),
+        InputKind::Mouse => Box::new(MouseInput::new(canvas.layout())),
+        InputKind::Script(path) => Box::new(load_script(path)?),
+    };
+    //只录第一局，这一局结束或中途退出时写入文件
+    let mut recording = seed
+        .filter(|_| options.record.is_some())
+        .map(|seed| Recording::new(mode, seed, half_block, game_state.get_game_historic_score()));
 
     while game_state.get_game_is_runing() {
         //统一获取输入，避免多次调用导致的状态不一致
+        input_device.sync_head(snake.get_head_position());
         let input = input_device.direction_check();
 
         //处理挂起（游戏结束/暂停）状态
         if game_state.get_game_is_suspend() {
-            canvas.render_canvas(&snake, &mut game_state);
+            canvas.render_canvas(renderer.as_mut(), &snake, &game_state)?;
+            //无界面模式没有人来按重新开始，游戏结束即退出
+            if headless {
+                break;
+            }
             //如果是重新开始，则恢复游戏初始化状态
             if let Some(Direction::Restart) = input {
-                let (i, g, s, c) = setup();
-                input_device = i;
+                let (g, s, c) = setup();
                 game_state = g;
                 snake = s;
                 canvas = c;
+                if let Some(telemetry) = telemetry.as_mut() {
+                    telemetry.start_game(
+                        mode,
+                        canvas.get_canvas_width(),
+                        canvas.get_canvas_height(),
+                    )?;
+                }
+                last_tick = Instant::now();
                 //清除一次屏幕
-                execute!(
-                    stdout(),
-                    Clear(ClearType::All), // 清除所有字符
-                    MoveTo(0, 0)           // 光标归位
-                )
-                .unwrap();
+                renderer.clear_screen()?;
             }
             if let Some(Direction::Quit) = input {
                 game_state.set_game_is_runing(false);
             }
-            thread::sleep(Duration::from_millis(250));
+            thread::sleep(tick_duration);
             continue; // 跳过本次循环后续逻辑
         }
 
         // 正常游戏逻辑：处理退出和移动
+        if let Some(recording) = recording.as_mut() {
+            recording.push(input);
+        }
         match input {
             Some(Direction::Quit) => {
+                save_recording(recording.take(), options)?;
                 game_state.set_game_is_runing(false);
                 continue;
             }
             Some(dir) => {
-                if let Some(new_dir) = snake.get_head_direction().apply(dir) {
-                    snake.set_head_direction(new_dir);
-                }
+                steer(&mut snake, &mut game_state, dir);
             }
             None => {}
         }
 
-        // 5. 更新状态与渲染
-        snake.snake_move(
-            &mut game_state,
-            canvas.get_canvas_width(),
-            canvas.get_canvas_height(),
-        );
-        canvas.render_canvas(&snake, &mut game_state);
+        // 5. 更新状态（移动、计时、模式胜负判定）与渲染
+        step(&mut snake, &mut game_state);
+        if let Some(telemetry) = telemetry.as_mut() {
+            let now = Instant::now();
+            let sample = TickSample::capture(&snake, &game_state, input, now - last_tick);
+            telemetry.record(&sample)?;
+            last_tick = now;
+        }
+        let events = game_state.drain_events();
+        event_bus.publish(game_state.get_game_tick(), &events)?;
+        //每日挑战每结束一局就记录一次成绩，脚本跑出来的对局不算
+        let finished = events
+            .iter()
+            .any(|e| matches!(e, GameEvent::Died { .. } | GameEvent::Won { .. }));
+        if let GameMode::Daily { date } = mode
+            && finished
+            && !headless
+        {
+            record_daily_result(date, &options.player, &game_state, &snake)?;
+        }
+        if game_state.get_game_is_suspend() {
+            save_recording(recording.take(), options)?;
+        }
+        canvas.render_canvas(renderer.as_mut(), &snake, &game_state)?;
 
-        thread::sleep(Duration::from_millis(250));
+        thread::sleep(tick_duration);
     }
 
     // 6. 游戏退出后的收尾
-    finalize_game(&mut game_state);
+    if headless {
+        writeln!(
+            io::stdout().lock(),
+            "{}",
+            summary_json(&game_state, &snake, seed)
+        )
+        .map_err(GameError::Terminal)?;
+        return Ok(());
+    }
+    finalize_game(&mut game_state, achievements.as_ref())
 }
 
-fn finalize_game(game_state: &mut game::GameState) {
-    game_state.save_historic_score();
-    println!("正在退出！");
+/// 成就页面：列出所有成就的解锁状态与进度
+pub fn show_achievements() -> Result<(), GameError> {
+    let report = Achievements::load(Achievements::FILE).report();
+    write!(io::stdout().lock(), "{}", report).map_err(GameError::Terminal)
+}
+
+fn save_recording(recording: Option<Recording>, options: &Options) -> Result<(), GameError> {
+    if let (Some(recording), Some(path)) = (recording, &options.record) {
+        fs::write(path, recording.to_script()).map_err(GameError::SaveData)?;
+    }
+    Ok(())
+}
+
+/// 按录像重新模拟整局游戏并导出成 asciinema 录像或 GIF 动画
+pub fn export_replay(
+    recording_path: &Path,
+    out: &Path,
+    format: ExportFormat,
+) -> Result<(), GameError> {
+    let text = fs::read_to_string(recording_path)
+        .map_err(|e| GameError::Script(format!("读取 {} 失败: {}", recording_path.display(), e)))?;
+    let replay = Recording::parse(&text).map_err(GameError::Script)?.replay();
+    match format {
+        ExportFormat::Cast => fs::write(out, replay.to_asciicast()).map_err(GameError::Export),
+        ExportFormat::Gif => {
+            let file = fs::File::create(out).map_err(GameError::Export)?;
+            replay.write_gif(io::BufWriter::new(file))
+        }
+    }
+}
+
+/// 分析遥测日志并打印报告
+pub fn analyze_telemetry(path: &Path) -> Result<(), GameError> {
+    let log = fs::read_to_string(path).map_err(GameError::Log)?;
+    let report = telemetry::analyze(&log).map_err(|msg| {
+        GameError::Log(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), msg),
+        ))
+    })?;
+    write!(io::stdout().lock(), "{}", report.to_text()).map_err(GameError::Terminal)
+}
+
+fn record_daily_result(
+    date: ChallengeDate,
+    player: &str,
+    game_state: &GameState,
+    snake: &Snake,
+) -> Result<(), GameError> {
+    let Some(outcome) = game_state.get_game_outcome() else {
+        return Ok(());
+    };
+    let result = DailyResult {
+        date,
+        player: player.to_string(),
+        score: game_state.get_game_score(),
+        ticks: game_state.get_game_tick(),
+        length: snake.get_snake_body().len(),
+        outcome,
+    };
+    daily::append_result(Path::new(RESULTS_FILE), &result)
+}
+
+/// 导出每日挑战成绩，未指定文件时输出到标准输出
+pub fn export_daily(date: Option<ChallengeDate>, out: Option<&Path>) -> Result<(), GameError> {
+    let results = daily::load_results(Path::new(RESULTS_FILE))?;
+    let csv = daily::export_csv(&results, date);
+    match out {
+        Some(path) => fs::write(path, csv).map_err(GameError::SaveData),
+        None => write!(io::stdout().lock(), "{}", csv).map_err(GameError::Terminal),
+    }
+}
+
+//读取输入脚本，路径为 "-" 时从标准输入读取
+fn load_script(path: &Path) -> Result<ScriptInput, GameError> {
+    let mut script = String::new();
+    let read_result = if path == Path::new("-") {
+        io::stdin().read_to_string(&mut script).map(|_| ())
+    } else {
+        fs::read_to_string(path).map(|content| script = content)
+    };
+    read_result.map_err(|e| GameError::Script(format!("读取 {} 失败: {}", path.display(), e)))?;
+    ScriptInput::parse(&script).map_err(GameError::Script)
+}
+
+//把最终成绩整理成一行 JSON，方便 CI 直接解析
+fn summary_json(game_state: &GameState, snake: &Snake, seed: Option<u64>) -> String {
+    let outcome = game_state
+        .get_game_outcome()
+        .map_or("null".to_string(), |o| format!("\"{}\"", o.id()));
+    let seed = seed.map_or("null".to_string(), |s| s.to_string());
+    format!(
+        "{{\"mode\":\"{}\",\"score\":{},\"ticks\":{},\"length\":{},\"outcome\":{},\"seed\":{}}}",
+        game_state.get_game_mode().name(),
+        game_state.get_game_score(),
+        game_state.get_game_tick(),
+        snake.get_snake_body().len(),
+        outcome,
+        seed
+    )
+}
+
+fn finalize_game(
+    game_state: &mut GameState,
+    achievements: Option<&Achievements>,
+) -> Result<(), GameError> {
+    game_state.save_historic_score()?;
+    //中途退出的对局也保留已经取得的进度
+    if let Some(achievements) = achievements {
+        achievements.finish(game_state.get_game_tick())?;
+    }
+    writeln!(io::stdout().lock(), "正在退出！").map_err(GameError::Terminal)?;
     thread::sleep(Duration::from_millis(3000));
+    Ok(())
 }
@@ -0,0 +1,294 @@
+/*
+在这个文件里面解析命令行参数，完整用法见 USAGE
+*/
+use my_rust_snake::{ChallengeDate, FeedbackStyle, GameMode};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+用法：my_rust_snake [选项]
+      my_rust_snake achievements            查看成就列表与进度
+      my_rust_snake daily-export [--date <YYYY-MM-DD>] [--out <文件>]
+                                            导出每日挑战成绩（CSV），默认导出所有日期到标准输出
+      my_rust_snake export <录像文件> --out <文件> [--format <cast|gif>]
+                                            把 --record 录下的对局导出成 asciinema 录像或 GIF 动画，
+                                            默认按输出文件的扩展名选择格式
+      my_rust_snake analyze <遥测日志>      统计 --telemetry 日志中的反应时间和死亡位置热力图
+  --mode <endless|time-attack|survival|daily>
+                                         游戏模式，默认 endless
+  --limit <N>                            限时挑战的秒数 / 生存挑战需要坚持的 tick 数
+  --renderer <terminal|ansi>             渲染后端，ansi 适合简陋终端，默认 terminal
+  --half-block                           用 ▀▄ 半格字符和颜色绘制 80x30 的棋盘，需要终端支持 ANSI 颜色
+                                         （不能用于每日挑战）
+  --input <keyboard|mouse>               输入设备，mouse 需要终端支持鼠标事件，默认 keyboard
+  --script <文件|->                      从文件或标准输入读取每个 tick 的指令（如 R R D2 .3 L）
+  --headless                             不进入原始模式和交替屏幕，不刷新画面，结束时输出 JSON 成绩
+                                         （必须配合 --script 使用）
+  --feedback <none|bell|flash>           吃到食物、升级、破纪录和结束时的反馈方式，默认 flash
+  --event-log <文件>                     把游戏事件以 JSON Lines 格式追加到文件
+  --seed <N>                             固定食物生成的随机种子，录像和复现对局时使用
+  --date <YYYY-MM-DD>                    每日挑战的日期（UTC），默认今天，不能晚于今天
+  --player <名字>                        记录每日挑战成绩时使用的名字，默认取环境变量 USER
+  --record <文件>                        把第一局的种子和每个 tick 的输入录下来，可以用 export 导出
+                                         （没有指定 --seed 时会随机选一个种子）
+  --telemetry <文件>                     把每个 tick 的蛇头、方向、输入、食物、分数和耗时以 JSON Lines 格式追加到文件";
+
+//渲染后端类型
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RendererKind {
+    Terminal,
+    Ansi,
+}
+
+//输入设备类型
+#[derive(Clone, PartialEq, Debug)]
+pub enum InputKind {
+    Keyboard,
+    Mouse,
+    /// 脚本路径，"-" 表示标准输入
+    Script(PathBuf),
+}
+
+/// 子命令：不带子命令时开始游戏
+pub enum Command {
+    Play(Options),
+    Achievements,
+    DailyExport {
+        date: Option<ChallengeDate>,
+        out: Option<PathBuf>,
+    },
+    Export {
+        recording: PathBuf,
+        out: PathBuf,
+        format: ExportFormat,
+    },
+    Analyze(PathBuf),
+}
+
+/// 回放导出格式
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ExportFormat {
+    /// asciinema v2 录像
+    Cast,
+    /// GIF 动画
+    Gif,
+}
+
+/// 命令行选项
+pub struct Options {
+    pub mode: GameMode,
+    pub renderer: RendererKind,
+    pub half_block: bool,
+    pub input: InputKind,
+    pub headless: bool,
+    pub feedback: Option<FeedbackStyle>,
+    pub event_log: Option<PathBuf>,
+    pub seed: Option<u64>,
+    pub player: String,
+    pub record: Option<PathBuf>,
+    pub telemetry: Option<PathBuf>,
+}
+
+/// 解析命令行参数（不包含程序名）
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    match args.first().map(String::as_str) {
+        Some("achievements") => match args.get(1) {
+            Some(extra) => Err(format!("achievements 不接受参数：{}", extra)),
+            None => Ok(Command::Achievements),
+        },
+        Some("daily-export") => parse_daily_export(&args[1..]),
+        Some("export") => parse_export(&args[1..]),
+        Some("analyze") => match &args[1..] {
+            [log] => Ok(Command::Analyze(log.into())),
+            _ => Err("analyze 需要且只需要一个遥测日志文件".to_string()),
+        },
+        _ => parse_options(args).map(Command::Play),
+    }
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut mode_name = String::from("endless");
+    let mut limit = None;
+    let mut renderer = RendererKind::Terminal;
+    let mut half_block = false;
+    let mut input = InputKind::Keyboard;
+    let mut headless = false;
+    let mut feedback = Some(FeedbackStyle::Flash);
+    let mut event_log = None;
+    let mut seed = None;
+    let mut date = None;
+    let mut player = None;
+    let mut record = None;
+    let mut telemetry = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--mode" => mode_name = next_value(&mut iter, "--mode")?.to_string(),
+            "--limit" => {
+                let value = next_value(&mut iter, "--limit")?;
+                let parsed = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("--limit 需要一个正整数，实际为：{}", value))?;
+                if parsed == 0 {
+                    return Err("--limit 必须大于 0".to_string());
+                }
+                limit = Some(parsed);
+            }
+            "--renderer" => {
+                renderer = match next_value(&mut iter, "--renderer")? {
+                    "terminal" => RendererKind::Terminal,
+                    "ansi" => RendererKind::Ansi,
+                    other => return Err(format!("未知的渲染后端：{}", other)),
+                }
+            }
+            "--half-block" => half_block = true,
+            "--input" => {
+                input = match next_value(&mut iter, "--input")? {
+                    "keyboard" => InputKind::Keyboard,
+                    "mouse" => InputKind::Mouse,
+                    other => return Err(format!("未知的输入设备：{}", other)),
+                }
+            }
+            "--script" => input = InputKind::Script(next_value(&mut iter, "--script")?.into()),
+            "--headless" => headless = true,
+            "--feedback" => {
+                feedback = match next_value(&mut iter, "--feedback")? {
+                    "none" => None,
+                    "bell" => Some(FeedbackStyle::Bell),
+                    "flash" => Some(FeedbackStyle::Flash),
+                    other => return Err(format!("未知的反馈方式：{}", other)),
+                }
+            }
+            "--event-log" => event_log = Some(next_value(&mut iter, "--event-log")?.into()),
+            "--seed" => {
+                let value = next_value(&mut iter, "--seed")?;
+                seed = Some(
+                    value
+                        .parse::<u64>()
+                        .map_err(|_| format!("--seed 需要一个非负整数，实际为：{}", value))?,
+                );
+            }
+            "--date" => date = Some(ChallengeDate::parse(next_value(&mut iter, "--date")?)?),
+            "--record" => record = Some(next_value(&mut iter, "--record")?.into()),
+            "--telemetry" => telemetry = Some(next_value(&mut iter, "--telemetry")?.into()),
+            "--player" => {
+                let name = next_value(&mut iter, "--player")?;
+                // 成绩以 CSV 保存，名字里不能有分隔符
+                if name.is_empty() || name.contains([',', '"', '\n', '\r']) {
+                    return Err(format!(
+                        "玩家名字不能为空，也不能包含逗号、引号或换行：{}",
+                        name
+                    ));
+                }
+                player = Some(name.to_string());
+            }
+            other => return Err(format!("无法识别的参数：{}", other)),
+        }
+    }
+
+    let mut mode = GameMode::from_name(&mode_name, limit)?;
+    if let Some(date) = date {
+        match mode {
+            // 不能提前玩还没到的挑战，否则可以先把成绩记进排行榜
+            GameMode::Daily { .. } if date > ChallengeDate::today() => {
+                return Err(format!("{} 的每日挑战还没有开始", date));
+            }
+            GameMode::Daily { .. } => mode = GameMode::Daily { date },
+            _ => return Err("--date 只能用于每日挑战（--mode daily）".to_string()),
+        }
+    }
+    // 每日挑战的障碍物和食物序列与棋盘大小有关，所有人必须用同样的 40x15 棋盘
+    if half_block && mode.daily_seed().is_some() {
+        return Err("每日挑战使用固定大小的棋盘，不能使用 --half-block".to_string());
+    }
+    if seed.is_some() && mode.daily_seed().is_some() {
+        return Err("每日挑战的种子由日期决定，不能再指定 --seed".to_string());
+    }
+
+    if headless && !matches!(input, InputKind::Script(_)) {
+        return Err("--headless 需要配合 --script 使用".to_string());
+    }
+
+    Ok(Options {
+        mode,
+        renderer,
+        half_block,
+        input,
+        headless,
+        feedback,
+        event_log,
+        seed,
+        player: player.unwrap_or_else(default_player),
+        record,
+        telemetry,
+    })
+}
+
+fn parse_export(args: &[String]) -> Result<Command, String> {
+    let mut recording = None;
+    let mut out: Option<PathBuf> = None;
+    let mut format = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--out" => out = Some(next_value(&mut iter, "--out")?.into()),
+            "--format" => {
+                format = Some(match next_value(&mut iter, "--format")? {
+                    "cast" => ExportFormat::Cast,
+                    "gif" => ExportFormat::Gif,
+                    other => return Err(format!("未知的导出格式：{}", other)),
+                })
+            }
+            other if other.starts_with("--") => {
+                return Err(format!("无法识别的参数：{}", other));
+            }
+            path if recording.is_none() => recording = Some(path.into()),
+            other => return Err(format!("export 只接受一个录像文件：{}", other)),
+        }
+    }
+    let recording = recording.ok_or("export 缺少录像文件")?;
+    let out = out.ok_or("export 需要用 --out 指定输出文件")?;
+    // 没有指定格式时按扩展名判断
+    let format = match format {
+        Some(format) => format,
+        None => match out.extension().and_then(|e| e.to_str()) {
+            Some("cast") => ExportFormat::Cast,
+            Some("gif") => ExportFormat::Gif,
+            _ => return Err("无法从输出文件的扩展名判断格式，请用 --format 指定".to_string()),
+        },
+    };
+    Ok(Command::Export {
+        recording,
+        out,
+        format,
+    })
+}
+
+fn parse_daily_export(args: &[String]) -> Result<Command, String> {
+    let mut date = None;
+    let mut out = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--date" => date = Some(ChallengeDate::parse(next_value(&mut iter, "--date")?)?),
+            "--out" => out = Some(next_value(&mut iter, "--out")?.into()),
+            other => return Err(format!("无法识别的参数：{}", other)),
+        }
+    }
+    Ok(Command::DailyExport { date, out })
+}
+
+//默认使用系统用户名，取不到时用 player
+fn default_player() -> String {
+    ["USER", "USERNAME"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .find(|name| !name.is_empty() && !name.contains([',', '"', '\n', '\r']))
+        .unwrap_or_else(|| "player".to_string())
+}
+
+fn next_value<'a>(iter: &mut std::slice::Iter<'a, String>, flag: &str) -> Result<&'a str, String> {
+    iter.next()
+        .map(String::as_str)
+        .ok_or_else(|| format!("{} 缺少参数值", flag))
+}
@@ -0,0 +1,435 @@
+/*
+在这个文件里面实现成就系统
+成就完全由事件流驱动：Achievements 作为事件订阅者记录每个成就的最好进度，
+进度达到目标即解锁，解锁提示显示在 UI 面板上，进度保存在 achievements.txt 中
+*/
+use crate::game::game_error::GameError;
+use crate::game::game_event::{EventSubscriber, GameEvent};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// 解锁提示在面板上停留的帧数（约 3 秒）
+const NOTICE_FRAMES: u32 = 12;
+
+/// 成就枚举定义
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Achievement {
+    /// 单局得到 10 分
+    FirstTenPoints,
+    /// 蛇身占满四分之一的棋盘
+    QuarterBoard,
+    /// 单局坚持 1000 个 tick
+    Marathon,
+    /// 一次都不向左转就赢下一局
+    NoLeftTurnWin,
+    /// 升到 5 级
+    LevelFive,
+    /// 打破历史最高分
+    RecordBreaker,
+}
+
+impl Achievement {
+    /// 所有成就，成就页面按这个顺序列出
+    pub const ALL: [Achievement; 6] = [
+        Achievement::FirstTenPoints,
+        Achievement::QuarterBoard,
+        Achievement::Marathon,
+        Achievement::NoLeftTurnWin,
+        Achievement::LevelFive,
+        Achievement::RecordBreaker,
+    ];
+
+    /// 成就的英文标识，也是存档中的键名
+    pub fn id(&self) -> &'static str {
+        match self {
+            Achievement::FirstTenPoints => "first_ten_points",
+            Achievement::QuarterBoard => "quarter_board",
+            Achievement::Marathon => "marathon",
+            Achievement::NoLeftTurnWin => "no_left_turn_win",
+            Achievement::LevelFive => "level_five",
+            Achievement::RecordBreaker => "record_breaker",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Achievement::FirstTenPoints => "初出茅庐",
+            Achievement::QuarterBoard => "四分天下",
+            Achievement::Marathon => "马拉松",
+            Achievement::NoLeftTurnWin => "绝不左转",
+            Achievement::LevelFive => "渐入佳境",
+            Achievement::RecordBreaker => "破纪录者",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::FirstTenPoints => "单局得到 10 分",
+            Achievement::QuarterBoard => "蛇身占满 25% 的棋盘",
+            Achievement::Marathon => "单局坚持 1000 个 tick",
+            Achievement::NoLeftTurnWin => "一次都不向左转就赢下一局",
+            Achievement::LevelFive => "升到 5 级",
+            Achievement::RecordBreaker => "打破任意模式的历史最高分",
+        }
+    }
+
+    /// 解锁需要达到的进度，QuarterBoard 的进度单位是百分比
+    pub fn goal(&self) -> u64 {
+        match self {
+            Achievement::FirstTenPoints => 10,
+            Achievement::QuarterBoard => 25,
+            Achievement::Marathon => 1000,
+            Achievement::NoLeftTurnWin => 1,
+            Achievement::LevelFive => 5,
+            Achievement::RecordBreaker => 1,
+        }
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|a| a == self).unwrap_or_default()
+    }
+}
+
+struct AchievementBook {
+    //每个成就的最好进度，不超过目标值
+    progress: [u64; Achievement::ALL.len()],
+    //存档路径，None 表示只保存在内存里
+    path: Option<PathBuf>,
+    //棋盘格子总数，用于计算蛇身占比
+    board_cells: usize,
+    //本局是否向左转过
+    turned_left: bool,
+    //等待显示的解锁提示，以及正在显示的提示和剩余帧数
+    notices: VecDeque<Achievement>,
+    current_notice: Option<Achievement>,
+    notice_frames: u32,
+}
+
+impl AchievementBook {
+    /// 更新进度，刚好解锁时返回 true
+    fn record(&mut self, achievement: Achievement, value: u64) -> bool {
+        let goal = achievement.goal();
+        let progress = &mut self.progress[achievement.index()];
+        if value <= *progress {
+            return false;
+        }
+        let newly_unlocked = *progress < goal && value >= goal;
+        *progress = value.min(goal);
+        if newly_unlocked {
+            self.notices.push_back(achievement);
+        }
+        newly_unlocked
+    }
+
+    /// 按蛇身占棋盘的百分比更新 QuarterBoard，未设置棋盘大小时不统计
+    fn record_length(&mut self, length: usize) -> bool {
+        match (length * 100).checked_div(self.board_cells) {
+            Some(percent) => self.record(Achievement::QuarterBoard, percent as u64),
+            None => false,
+        }
+    }
+}
+
+/// 成就进度：克隆出来的句柄共享同一份数据，
+/// 一份作为事件订阅者交给 EventBus，一份交给画布显示解锁提示；
+/// 用 Arc<Mutex> 共享，画布可以连同成就一起交给别的线程
+#[derive(Clone)]
+pub struct Achievements(Arc<Mutex<AchievementBook>>);
+
+impl Achievements {
+    pub const FILE: &'static str = "achievements.txt";
+
+    /// 从存档文件读取进度，文件不存在或某一行无法解析时按 0 处理
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let achievements = Self::with_path(Some(path.clone()));
+        if let Ok(content) = fs::read_to_string(&path) {
+            let mut book = achievements.book();
+            for line in content.lines() {
+                if let Some((key, value)) = line.split_once(':')
+                    && let Some(achievement) =
+                        Achievement::ALL.iter().find(|a| a.id() == key.trim())
+                {
+                    let value = value.trim().parse::<u64>().unwrap_or(0);
+                    book.progress[achievement.index()] = value.min(achievement.goal());
+                }
+            }
+        }
+        achievements
+    }
+
+    fn book(&self) -> MutexGuard<'_, AchievementBook> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 不读写存档文件的成就进度
+    pub fn in_memory() -> Self {
+        Self::with_path(None)
+    }
+
+    fn with_path(path: Option<PathBuf>) -> Self {
+        Achievements(Arc::new(Mutex::new(AchievementBook {
+            progress: [0; Achievement::ALL.len()],
+            path,
+            board_cells: 0,
+            turned_left: false,
+            notices: VecDeque::new(),
+            current_notice: None,
+            notice_frames: 0,
+        })))
+    }
+
+    /// 设置棋盘格子总数，未设置时不会统计蛇身占比
+    pub fn set_board_cells(&self, cells: usize) {
+        self.book().board_cells = cells;
+    }
+
+    pub fn progress(&self, achievement: Achievement) -> u64 {
+        self.book().progress[achievement.index()]
+    }
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.progress(achievement) >= achievement.goal()
+    }
+
+    /// 把所有进度写回存档，每个成就一行 "id: 进度"
+    pub fn save(&self) -> Result<(), GameError> {
+        let book = self.book();
+        let Some(path) = &book.path else {
+            return Ok(());
+        };
+        let content: String = Achievement::ALL
+            .iter()
+            .map(|a| format!("{}: {}\n", a.id(), book.progress[a.index()]))
+            .collect();
+        fs::write(path, content).map_err(GameError::SaveData)
+    }
+
+    /// 成就页面：列出所有成就的解锁状态与进度
+    pub fn report(&self) -> String {
+        let unlocked = Achievement::ALL
+            .iter()
+            .filter(|a| self.is_unlocked(**a))
+            .count();
+        let mut report = format!("成就（已解锁 {}/{}）\n", unlocked, Achievement::ALL.len());
+        for achievement in Achievement::ALL {
+            let mark = if self.is_unlocked(achievement) {
+                "✓"
+            } else {
+                " "
+            };
+            report.push_str(&format!(
+                "[{}] {}：{}（{}/{}）\n",
+                mark,
+                achievement.title(),
+                achievement.description(),
+                self.progress(achievement),
+                achievement.goal()
+            ));
+        }
+        report
+    }
+
+    /// 游戏退出时调用：中途退出的一局不会有结束事件，用最后的 tick 数补记 Marathon，再保存进度
+    pub fn finish(&self, tick: u64) -> Result<(), GameError> {
+        self.book().record(Achievement::Marathon, tick);
+        self.save()
+    }
+
+    /// 每帧调用一次，返回当前需要显示的解锁提示；多个提示依次显示
+    pub fn next_notice(&self) -> Option<Achievement> {
+        let mut book = self.book();
+        if book.notice_frames == 0 {
+            book.current_notice = book.notices.pop_front();
+            if book.current_notice.is_some() {
+                book.notice_frames = NOTICE_FRAMES;
+            }
+        }
+        let notice = book.current_notice?;
+        book.notice_frames -= 1;
+        Some(notice)
+    }
+}
+
+impl EventSubscriber for Achievements {
+    fn on_event(&mut self, tick: u64, event: &GameEvent) -> Result<(), GameError> {
+        let mut book = self.book();
+        let mut unlocked = book.record(Achievement::Marathon, tick);
+        let mut run_finished = false;
+        match *event {
+            GameEvent::FoodEaten { score, length, .. } => {
+                unlocked |= book.record(Achievement::FirstTenPoints, score as u64);
+                unlocked |= book.record_length(length);
+            }
+            GameEvent::Grew { length } => unlocked |= book.record_length(length),
+            GameEvent::Turned { from, to } => {
+                if from.turn_left() == Some(to) {
+                    book.turned_left = true;
+                }
+            }
+            GameEvent::LevelUp { level } => {
+                unlocked |= book.record(Achievement::LevelFive, level as u64);
+            }
+            // 新玩家或者新模式没有记录可破，第一次得分不算
+            GameEvent::NewHighScore { previous, .. } if previous > 0 => {
+                unlocked |= book.record(Achievement::RecordBreaker, 1);
+            }
+            GameEvent::NewHighScore { .. } => {}
+            GameEvent::Won { .. } => {
+                if !book.turned_left {
+                    unlocked |= book.record(Achievement::NoLeftTurnWin, 1);
+                }
+                run_finished = true;
+            }
+            GameEvent::Died { .. } => run_finished = true,
+        }
+        if run_finished {
+            // 一局结束，下一局重新统计是否左转
+            book.turned_left = false;
+        }
+        drop(book);
+        // 解锁时立即保存，避免强制退出时丢失；进度在每局结束时保存
+        if unlocked || run_finished {
+            self.save()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game_direction::Direction;
+    use crate::game::game_mode::GameOutcome;
+    use crate::game::snake::Position;
+
+    fn food(score: usize, length: usize) -> GameEvent {
+        GameEvent::FoodEaten {
+            score,
+            length,
+            position: Position { x: 0, y: 0 },
+        }
+    }
+
+    #[test]
+    fn events_unlock_achievements_and_show_notices() {
+        let mut achievements = Achievements::in_memory();
+        achievements.set_board_cells(40);
+        achievements.on_event(30, &food(9, 9)).unwrap();
+        assert_eq!(achievements.progress(Achievement::FirstTenPoints), 9);
+        assert_eq!(achievements.progress(Achievement::QuarterBoard), 22);
+        assert_eq!(achievements.next_notice(), None);
+
+        achievements.on_event(31, &food(10, 10)).unwrap();
+        assert!(achievements.is_unlocked(Achievement::FirstTenPoints));
+        assert!(achievements.is_unlocked(Achievement::QuarterBoard));
+        assert!(!achievements.is_unlocked(Achievement::Marathon));
+
+        // 两个提示依次显示，各停留 NOTICE_FRAMES 帧
+        let notices: Vec<_> = (0..NOTICE_FRAMES * 2 + 1)
+            .map(|_| achievements.next_notice())
+            .collect();
+        assert_eq!(notices[0], Some(Achievement::FirstTenPoints));
+        assert_eq!(
+            notices[NOTICE_FRAMES as usize],
+            Some(Achievement::QuarterBoard)
+        );
+        assert_eq!(notices[NOTICE_FRAMES as usize * 2], None);
+
+        // 已经解锁的成就不会重复提示
+        achievements.on_event(1000, &food(11, 11)).unwrap();
+        assert_eq!(achievements.next_notice(), Some(Achievement::Marathon));
+    }
+
+    #[test]
+    fn growth_and_quitting_still_count() {
+        let mut achievements = Achievements::in_memory();
+        achievements.set_board_cells(40);
+
+        // 生存挑战自动增长没有 FoodEaten，也要统计蛇身占比
+        achievements
+            .on_event(200, &GameEvent::Grew { length: 10 })
+            .unwrap();
+        assert!(achievements.is_unlocked(Achievement::QuarterBoard));
+
+        // 中途退出没有结束事件，退出时补记坚持的 tick 数
+        achievements.finish(640).unwrap();
+        assert_eq!(achievements.progress(Achievement::Marathon), 640);
+    }
+
+    #[test]
+    fn handles_share_progress_across_threads() {
+        let achievements = Achievements::in_memory();
+        let mut subscriber = achievements.clone();
+        std::thread::spawn(move || subscriber.on_event(1, &food(10, 3)).unwrap())
+            .join()
+            .unwrap();
+        assert!(achievements.is_unlocked(Achievement::FirstTenPoints));
+    }
+
+    #[test]
+    fn record_breaker_needs_an_existing_record() {
+        let mut achievements = Achievements::in_memory();
+        let high_score = |score, previous| GameEvent::NewHighScore { score, previous };
+
+        achievements.on_event(10, &high_score(1, 0)).unwrap();
+        assert!(!achievements.is_unlocked(Achievement::RecordBreaker));
+
+        achievements.on_event(90, &high_score(8, 7)).unwrap();
+        assert!(achievements.is_unlocked(Achievement::RecordBreaker));
+    }
+
+    #[test]
+    fn winning_without_left_turns() {
+        let mut achievements = Achievements::in_memory();
+        let turn = |from, to| GameEvent::Turned { from, to };
+        let won = GameEvent::Won {
+            outcome: GameOutcome::TimeUp,
+        };
+
+        // 向左转过的一局不算
+        achievements
+            .on_event(1, &turn(Direction::Right, Direction::Up))
+            .unwrap();
+        achievements.on_event(2, &won).unwrap();
+        assert!(!achievements.is_unlocked(Achievement::NoLeftTurnWin));
+
+        // 下一局只向右转
+        achievements
+            .on_event(1, &turn(Direction::Right, Direction::Down))
+            .unwrap();
+        achievements
+            .on_event(2, &turn(Direction::Down, Direction::Left))
+            .unwrap();
+        achievements.on_event(3, &won).unwrap();
+        assert!(achievements.is_unlocked(Achievement::NoLeftTurnWin));
+    }
+
+    #[test]
+    fn progress_survives_reload() {
+        let path = std::env::temp_dir().join(format!(
+            "my_rust_snake_achievements_{}.txt",
+            std::process::id()
+        ));
+        let mut achievements = Achievements::load(&path);
+        achievements.on_event(120, &food(4, 7)).unwrap();
+        achievements
+            .on_event(
+                121,
+                &GameEvent::Died {
+                    outcome: GameOutcome::HitWall,
+                },
+            )
+            .unwrap();
+
+        let reloaded = Achievements::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(reloaded.progress(Achievement::FirstTenPoints), 4);
+        assert_eq!(reloaded.progress(Achievement::Marathon), 121);
+        let report = reloaded.report();
+        assert!(report.starts_with("成就（已解锁 0/6）\n"));
+        assert!(report.contains("[ ] 初出茅庐：单局得到 10 分（4/10）\n"));
+    }
+}
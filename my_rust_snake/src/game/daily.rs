@@ -0,0 +1,336 @@
+/*
+在这个文件里面实现每日挑战
+同一天的随机种子由日期（UTC）决定，食物位置和障碍物布局都从这个种子生成，
+所以同一天所有人拿到的都是同一张棋盘；每局成绩按日期追加到 daily_results.csv，
+可以按日期导出成带排名的 CSV 互相比较
+*/
+use crate::game::game_error::GameError;
+use crate::game::game_mode::GameOutcome;
+use crate::game::snake::Position;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 每日挑战成绩的存档文件
+pub const RESULTS_FILE: &str = "daily_results.csv";
+/// 存档与导出共用的表头，导出时在日期后面多一列排名
+const RESULTS_HEADER: &str = "date,player,score,ticks,length,outcome";
+/// 障碍物与食物使用不同的随机序列，避免两者的位置相关
+const OBSTACLE_SALT: u64 = 0x9e37_79b9_7f4a_7c15;
+/// 平均每多少个格子放一段障碍物
+const CELLS_PER_OBSTACLE: usize = 75;
+/// 蛇头正前方保持空旷的格子数，保证开局不会马上撞上障碍物
+const SAFE_AHEAD: i32 = 6;
+
+/// 挑战日期（UTC）
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct ChallengeDate {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+impl ChallengeDate {
+    /// 今天的日期（UTC），全队不论身处哪个时区都在同一时刻换题
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self::from_days((seconds / 86_400) as i64)
+    }
+
+    /// 从 1970-01-01 起的天数换算成日期
+    pub fn from_days(days: i64) -> Self {
+        // 以 0000-03-01 为起点按 400 年周期换算，闰日正好落在每年最后一天
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        let year = (year_of_era + era * 400) as i32 + i32::from(month <= 2);
+        ChallengeDate { year, month, day }
+    }
+
+    /// 解析 YYYY-MM-DD 格式的日期
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || format!("日期格式应为 YYYY-MM-DD，实际为：{}", text);
+        let mut parts = text.trim().splitn(3, '-');
+        let mut next = || parts.next().and_then(|p| p.parse::<u32>().ok());
+        let (Some(year), Some(month), Some(day)) = (next(), next(), next()) else {
+            return Err(invalid());
+        };
+        let date = ChallengeDate {
+            year: year as i32,
+            month,
+            day,
+        };
+        if !(1..=12).contains(&month) || day == 0 || day > date.days_in_month() {
+            return Err(invalid());
+        }
+        Ok(date)
+    }
+
+    fn days_in_month(&self) -> u32 {
+        match self.month {
+            2 if self.year % 4 == 0 && (self.year % 100 != 0 || self.year % 400 == 0) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    /// 当天的随机种子：对日期文本做 FNV-1a 哈希，不依赖标准库哈希算法的实现细节
+    pub fn seed(&self) -> u64 {
+        self.to_string()
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+}
+
+impl fmt::Display for ChallengeDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// 根据种子生成障碍物布局：若干段横向或纵向的短墙
+/// 蛇身周围和蛇头正前方的格子保持空旷
+pub fn obstacle_layout(
+    seed: u64,
+    width: usize,
+    height: usize,
+    body: &[Position],
+    head_direction: (i32, i32),
+) -> Vec<Position> {
+    let mut rng = StdRng::seed_from_u64(seed ^ OBSTACLE_SALT);
+    let head = body.first().copied().unwrap_or(Position { x: 0, y: 0 });
+    let is_safe = |p: &Position| {
+        let near_body = body
+            .iter()
+            .any(|b| (b.x - p.x).abs() <= 2 && (b.y - p.y).abs() <= 2);
+        let ahead = (1..=SAFE_AHEAD)
+            .any(|i| p.x == head.x + head_direction.0 * i && p.y == head.y + head_direction.1 * i);
+        near_body || ahead
+    };
+
+    let mut obstacles = Vec::new();
+    for _ in 0..(width * height) / CELLS_PER_OBSTACLE {
+        let length = rng.gen_range(3..=5);
+        let (dx, dy) = if rng.gen_bool(0.5) { (1, 0) } else { (0, 1) };
+        let start = Position {
+            x: rng.gen_range(0..width) as i32,
+            y: rng.gen_range(0..height) as i32,
+        };
+        obstacles.extend(
+            (0..length)
+                .map(|i| Position {
+                    x: start.x + dx * i,
+                    y: start.y + dy * i,
+                })
+                .filter(|p| (p.x as usize) < width && (p.y as usize) < height && !is_safe(p)),
+        );
+    }
+    obstacles
+}
+
+/// 一局每日挑战的成绩
+#[derive(Clone, PartialEq, Debug)]
+pub struct DailyResult {
+    pub date: ChallengeDate,
+    pub player: String,
+    pub score: usize,
+    pub ticks: u64,
+    pub length: usize,
+    pub outcome: GameOutcome,
+}
+
+impl DailyResult {
+    /// 转换成一行 CSV（不含换行），玩家名在命令行解析时已经排除了逗号和引号
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{}",
+            self.date,
+            self.player,
+            self.score,
+            self.ticks,
+            self.length,
+            self.outcome.id()
+        )
+    }
+
+    /// 解析 to_csv 生成的一行
+    pub fn from_csv(line: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = line.split(',').collect();
+        let [date, player, score, ticks, length, outcome] = fields[..] else {
+            return Err(format!("应有 6 列，实际为 {} 列", fields.len()));
+        };
+        let number = |text: &str| {
+            text.parse::<u64>()
+                .map_err(|_| format!("无法解析的数字：{}", text))
+        };
+        Ok(DailyResult {
+            date: ChallengeDate::parse(date)?,
+            player: player.to_string(),
+            score: number(score)? as usize,
+            ticks: number(ticks)?,
+            length: number(length)? as usize,
+            outcome: GameOutcome::from_id(outcome)
+                .ok_or_else(|| format!("未知的结局：{}", outcome))?,
+        })
+    }
+}
+
+/// 把一局成绩追加到存档，文件不存在时先写表头
+pub fn append_result(path: &Path, result: &DailyResult) -> Result<(), GameError> {
+    let is_new = fs::metadata(path).map_or(true, |m| m.len() == 0);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(GameError::SaveData)?;
+    if is_new {
+        writeln!(file, "{}", RESULTS_HEADER).map_err(GameError::SaveData)?;
+    }
+    writeln!(file, "{}", result.to_csv()).map_err(GameError::SaveData)
+}
+
+/// 读取所有成绩，文件不存在时返回空列表
+pub fn load_results(path: &Path) -> Result<Vec<DailyResult>, GameError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(GameError::SaveData(e)),
+    };
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && *line != RESULTS_HEADER)
+        .map(|(i, line)| {
+            DailyResult::from_csv(line).map_err(|msg| {
+                let msg = format!("{} 第 {} 行: {}", path.display(), i + 1, msg);
+                GameError::SaveData(io::Error::new(io::ErrorKind::InvalidData, msg))
+            })
+        })
+        .collect()
+}
+
+/// 导出成绩：可以只导出某一天，同一天内按分数从高到低排名，分数相同时用时少的在前
+pub fn export_csv(results: &[DailyResult], date: Option<ChallengeDate>) -> String {
+    let mut selected: Vec<&DailyResult> = results
+        .iter()
+        .filter(|r| date.is_none_or(|d| r.date == d))
+        .collect();
+    selected.sort_by(|a, b| {
+        a.date
+            .cmp(&b.date)
+            .then(b.score.cmp(&a.score))
+            .then(a.ticks.cmp(&b.ticks))
+    });
+
+    let mut csv = RESULTS_HEADER.replacen("date,", "date,rank,", 1);
+    csv.push('\n');
+    let mut rank = 0;
+    let mut previous_date = None;
+    for result in selected {
+        if previous_date != Some(result.date) {
+            previous_date = Some(result.date);
+            rank = 0;
+        }
+        rank += 1;
+        let line = result.to_csv();
+        let (date, rest) = line.split_once(',').unwrap_or_default();
+        csv.push_str(&format!("{},{},{}\n", date, rank, rest));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> ChallengeDate {
+        ChallengeDate::parse(text).unwrap()
+    }
+
+    fn result(day: &str, player: &str, score: usize, ticks: u64) -> DailyResult {
+        DailyResult {
+            date: date(day),
+            player: player.to_string(),
+            score,
+            ticks,
+            length: score + 3,
+            outcome: GameOutcome::HitWall,
+        }
+    }
+
+    #[test]
+    fn dates_convert_and_parse() {
+        assert_eq!(ChallengeDate::from_days(0).to_string(), "1970-01-01");
+        assert_eq!(ChallengeDate::from_days(11_016).to_string(), "2000-02-29");
+        assert_eq!(ChallengeDate::from_days(20_744).to_string(), "2026-10-18");
+        assert_eq!(date("2024-02-29").to_string(), "2024-02-29");
+        assert!(ChallengeDate::parse("2023-02-29").is_err());
+        assert!(ChallengeDate::parse("2026-13-01").is_err());
+        assert!(ChallengeDate::parse("20261018").is_err());
+    }
+
+    #[test]
+    fn same_date_gives_same_board() {
+        let body = [Position { x: 5, y: 5 }, Position { x: 4, y: 5 }];
+        let layout = |day: &str| obstacle_layout(date(day).seed(), 40, 15, &body, (1, 0));
+        assert_eq!(layout("2026-10-18"), layout("2026-10-18"));
+        assert_ne!(layout("2026-10-18"), layout("2026-10-19"));
+
+        let obstacles = layout("2026-10-18");
+        assert!(!obstacles.is_empty());
+        for p in &obstacles {
+            assert!(p.x >= 0 && p.x < 40 && p.y >= 0 && p.y < 15);
+            assert!(
+                !(p.y == 5 && (3..=11).contains(&p.x)),
+                "开局路线上有障碍物：{:?}",
+                p
+            );
+        }
+    }
+
+    #[test]
+    fn results_round_trip_and_export_ranked() {
+        let path =
+            std::env::temp_dir().join(format!("my_rust_snake_daily_{}.csv", std::process::id()));
+        let results = [
+            result("2026-10-18", "alice", 12, 300),
+            result("2026-10-17", "bob", 3, 40),
+            result("2026-10-18", "bob", 20, 500),
+            result("2026-10-18", "carol", 12, 250),
+        ];
+        for r in &results {
+            append_result(&path, r).unwrap();
+        }
+        let loaded = load_results(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, results);
+
+        assert_eq!(
+            export_csv(&loaded, Some(date("2026-10-18"))),
+            "date,rank,player,score,ticks,length,outcome\n\
+             2026-10-18,1,bob,20,500,23,hit_wall\n\
+             2026-10-18,2,carol,12,250,15,hit_wall\n\
+             2026-10-18,3,alice,12,300,15,hit_wall\n"
+        );
+        assert!(export_csv(&loaded, None).contains("\n2026-10-17,1,bob,3,40,6,hit_wall\n"));
+    }
+}
@@ -1,7 +1,10 @@
-use crossterm::event::{self, Event, KeyCode, read};
+use crate::game::game_display::BoardLayout;
+use crate::game::snake::Position;
+use crossterm::event::{self, Event, KeyCode, MouseButton, MouseEventKind, read};
+use std::collections::VecDeque;
 use std::time::Duration;
 
-//方向枚举定义
+/// 方向枚举定义，Quit 与 Restart 是输入设备发出的控制指令
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Direction {
     Up,
@@ -13,8 +16,8 @@ pub enum Direction {
 }
 
 impl Direction {
-    //方向冲突检测函数，如果新方向与当前方向冲突则返回true，否则返回false
-    fn is_opposite(&self, other: Direction) -> bool {
+    /// 方向冲突检测函数，如果新方向与当前方向冲突则返回true，否则返回false
+    pub fn is_opposite(&self, other: Direction) -> bool {
         matches!(
             (self, other),
             (Direction::Up, Direction::Down)
@@ -24,7 +27,7 @@ impl Direction {
         )
     }
 
-    //方向安全更新
+    /// 方向安全更新：掉头、重复方向以及非方向指令都返回 None
     pub fn apply(self, next: Direction) -> Option<Direction> {
         // 1. 首先排除非方向性的指令
         match next {
@@ -39,7 +42,28 @@ impl Direction {
             _ => None, // 如果是 Restart 或 Quit，直接视为无效转向
         }
     }
-    //向量转换
+    /// 相对当前方向向左转之后的方向，非方向指令返回 None
+    pub fn turn_left(self) -> Option<Direction> {
+        match self {
+            Direction::Up => Some(Direction::Left),
+            Direction::Left => Some(Direction::Down),
+            Direction::Down => Some(Direction::Right),
+            Direction::Right => Some(Direction::Up),
+            _ => None,
+        }
+    }
+    /// 指令的英文标识，用于导出 JSON 等机器可读的格式
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Up => "up",
+            Direction::Down => "down",
+            Direction::Left => "left",
+            Direction::Right => "right",
+            Direction::Quit => "quit",
+            Direction::Restart => "restart",
+        }
+    }
+    /// 向量转换：返回 (dx, dy)，非方向指令为 (0, 0)
     pub fn to_vec(self) -> (i32, i32) {
         match self {
             //up,down对应画布的Y轴
@@ -53,11 +77,28 @@ impl Direction {
     }
 }
 
-//输入设备特征定义
+/// 输入设备特征定义
 pub trait InputDevice {
+    /// 每个 tick 调用一次，返回这段时间内的最新指令，没有输入时返回 None
     fn direction_check(&mut self) -> Option<Direction>;
+    /// 每帧告知输入设备当前蛇头位置，需要相对蛇头判断方向的设备（如鼠标）会用到
+    fn sync_head(&mut self, _head: Position) {}
 }
 
+//按键与指令的对应关系，键盘和鼠标输入共用
+fn key_to_direction(code: KeyCode) -> Option<Direction> {
+    match code {
+        KeyCode::Char('w') | KeyCode::Up => Some(Direction::Up),
+        KeyCode::Char('s') | KeyCode::Down => Some(Direction::Down),
+        KeyCode::Char('a') | KeyCode::Left => Some(Direction::Left),
+        KeyCode::Char('d') | KeyCode::Right => Some(Direction::Right),
+        KeyCode::Char('q') => Some(Direction::Quit),
+        KeyCode::Char('r') => Some(Direction::Restart),
+        _ => None,
+    }
+}
+
+/// 键盘输入：WSAD 或方向键移动，Q 退出，R 重新开始
 pub struct KeyboardInput;
 
 impl InputDevice for KeyboardInput {
@@ -65,21 +106,222 @@ impl InputDevice for KeyboardInput {
         let mut last_dir = None;
         // 只要缓冲区有东西，就一直读，读到没东西为止
         while event::poll(Duration::from_millis(0)).ok()? {
-            if let Event::Key(key) = read().ok()? {
-                let dir = match key.code {
-                    KeyCode::Char('w') | KeyCode::Up => Some(Direction::Up),
-                    KeyCode::Char('s') | KeyCode::Down => Some(Direction::Down),
-                    KeyCode::Char('a') | KeyCode::Left => Some(Direction::Left),
-                    KeyCode::Char('d') | KeyCode::Right => Some(Direction::Right),
-                    KeyCode::Char('q') => Some(Direction::Quit),
-                    KeyCode::Char('r') => Some(Direction::Restart),
-                    _ => None,
-                };
-                if dir.is_some() {
-                    last_dir = dir;
+            if let Event::Key(key) = read().ok()?
+                && let Some(dir) = key_to_direction(key.code)
+            {
+                last_dir = Some(dir);
+            }
+        }
+        last_dir
+    }
+}
+
+/// 鼠标输入：左键点击蛇头的上下左右即可转向，也可以点击地图下方的方向按钮
+/// 需要终端开启鼠标事件上报；键盘按键依然可用
+pub struct MouseInput {
+    layout: BoardLayout,
+    head: Position,
+}
+
+impl MouseInput {
+    /// layout 应来自实际渲染画面的 [`crate::game::game_display::Canvas::layout`]
+    pub fn new(layout: BoardLayout) -> Self {
+        MouseInput {
+            layout,
+            head: Position { x: 0, y: 0 },
+        }
+    }
+
+    //把一次点击换算成方向：优先看按钮，其次看点击位置相对蛇头偏向哪个轴
+    fn click_to_direction(&self, column: u16, row: u16) -> Option<Direction> {
+        if let Some(dir) = self.layout.button_at(column, row) {
+            return Some(dir);
+        }
+        let cell = self.layout.screen_to_cell(column, row)?;
+        let (dx, dy) = (cell.x - self.head.x, cell.y - self.head.y);
+        match (dx, dy) {
+            (0, 0) => None,
+            _ if dx.abs() >= dy.abs() => Some(if dx > 0 {
+                Direction::Right
+            } else {
+                Direction::Left
+            }),
+            _ => Some(if dy > 0 {
+                Direction::Down
+            } else {
+                Direction::Up
+            }),
+        }
+    }
+}
+
+impl InputDevice for MouseInput {
+    fn direction_check(&mut self) -> Option<Direction> {
+        let mut last_dir = None;
+        while event::poll(Duration::from_millis(0)).ok()? {
+            let dir = match read().ok()? {
+                Event::Key(key) => key_to_direction(key.code),
+                Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                    self.click_to_direction(mouse.column, mouse.row)
                 }
+                _ => None,
+            };
+            if dir.is_some() {
+                last_dir = dir;
             }
         }
         last_dir
     }
+
+    fn sync_head(&mut self, head: Position) {
+        self.head = head;
+    }
+}
+
+/// 脚本输入：每个 tick 读取脚本中的一条指令，用于自动化演示和 CI
+/// 脚本由空白分隔的指令组成，# 之后到行尾为注释：
+/// U D L R 表示转向，. 表示本 tick 不操作，Q 表示退出
+/// 指令后可以跟重复次数，例如 R3 等价于 R R R，.10 表示连续 10 个 tick 不操作
+/// 脚本执行完毕后自动发出退出指令
+#[derive(Debug)]
+pub struct ScriptInput {
+    // (指令, 剩余重复次数)
+    steps: VecDeque<(Option<Direction>, usize)>,
+}
+
+impl ScriptInput {
+    /// 解析脚本文本，格式错误时返回带行号的中文描述
+    pub fn parse(script: &str) -> Result<ScriptInput, String> {
+        let mut steps = VecDeque::new();
+        for (line_index, line) in script.lines().enumerate() {
+            let code = line.split('#').next().unwrap_or_default();
+            for token in code.split_whitespace() {
+                let step = Self::parse_token(token)
+                    .map_err(|e| format!("第 {} 行 `{}`：{}", line_index + 1, token, e))?;
+                steps.push_back(step);
+            }
+        }
+        Ok(ScriptInput { steps })
+    }
+
+    fn parse_token(token: &str) -> Result<(Option<Direction>, usize), String> {
+        let mut chars = token.chars();
+        let command = chars.next().unwrap_or_default();
+        let direction = match command.to_ascii_uppercase() {
+            'U' => Some(Direction::Up),
+            'D' => Some(Direction::Down),
+            'L' => Some(Direction::Left),
+            'R' => Some(Direction::Right),
+            'Q' => Some(Direction::Quit),
+            '.' => None,
+            _ => return Err("未知指令，只支持 U D L R . Q".to_string()),
+        };
+        let repeat = chars.as_str();
+        let count = if repeat.is_empty() {
+            1
+        } else {
+            repeat
+                .parse::<usize>()
+                .ok()
+                .filter(|count| *count > 0)
+                .ok_or("重复次数必须是正整数")?
+        };
+        Ok((direction, count))
+    }
+}
+
+impl InputDevice for ScriptInput {
+    fn direction_check(&mut self) -> Option<Direction> {
+        let Some((direction, remaining)) = self.steps.front_mut() else {
+            return Some(Direction::Quit);
+        };
+        let direction = *direction;
+        *remaining -= 1;
+        if *remaining == 0 {
+            self.steps.pop_front();
+        }
+        direction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Direction; 6] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+        Direction::Quit,
+        Direction::Restart,
+    ];
+
+    #[test]
+    fn apply_never_allows_reversal() {
+        for current in ALL {
+            for next in ALL {
+                if let Some(result) = current.apply(next) {
+                    assert!(!current.is_opposite(result), "{:?} -> {:?}", current, next);
+                    assert_ne!(result, current);
+                    assert_ne!(result.to_vec(), (0, 0), "非方向指令不能成为新方向");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mouse_click_steers_relative_to_head() {
+        use crate::game::game_display::Canvas;
+
+        let layout = Canvas::new().layout();
+        let mut mouse = MouseInput::new(layout);
+        mouse.sync_head(Position { x: 10, y: 5 });
+        // 格子 (x, y) 位于第 1 + 2x 列、第 1 + y 行
+        let click = |x: u16, y: u16| mouse.click_to_direction(1 + 2 * x, 1 + y);
+        assert_eq!(click(15, 6), Some(Direction::Right));
+        assert_eq!(click(3, 4), Some(Direction::Left));
+        assert_eq!(click(11, 12), Some(Direction::Down));
+        assert_eq!(click(9, 0), Some(Direction::Up));
+        assert_eq!(click(10, 5), None);
+        // 第三个按钮 "[  <  ]" 从第 20 列开始
+        assert_eq!(
+            mouse.click_to_direction(23, layout.button_row()),
+            Some(Direction::Left)
+        );
+    }
+
+    #[test]
+    fn script_repeats_and_quits_when_exhausted() {
+        let mut script = ScriptInput::parse("R2 d # 向下\n.2 l\n").unwrap();
+        let inputs: Vec<_> = (0..8).map(|_| script.direction_check()).collect();
+        assert_eq!(
+            inputs,
+            [
+                Some(Direction::Right),
+                Some(Direction::Right),
+                Some(Direction::Down),
+                None,
+                None,
+                Some(Direction::Left),
+                Some(Direction::Quit),
+                Some(Direction::Quit),
+            ]
+        );
+    }
+
+    #[test]
+    fn script_rejects_bad_tokens() {
+        assert!(
+            ScriptInput::parse("R X")
+                .unwrap_err()
+                .contains("第 1 行 `X`")
+        );
+        assert!(
+            ScriptInput::parse("U\nR0")
+                .unwrap_err()
+                .contains("第 2 行 `R0`")
+        );
+        assert!(ScriptInput::parse("Rx").is_err());
+    }
 }
@@ -5,25 +5,167 @@ y(行号)：代表你前面有多少个“完整的行”。
 W(宽度)：每一行包含的元素个数。
 x (列号)：代表你在当前这一行往后偏移了多少个位置。
 
+半格模式：每个格子只占一列，上下两行格子合并成一个终端行，
+用 ▀ ▄ 字符加前景色/背景色画出来，同样大小的终端里棋盘的高度可以翻倍
 */
 use crate::game::GameState;
+use crate::game::achievement::Achievements;
+use crate::game::game_direction::Direction;
+use crate::game::game_error::GameError;
+use crate::game::game_renderer::Renderer;
 use crate::game::snake::{self, Position};
-use crossterm::{cursor, execute};
-use std::io::{Write, stdout};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// 鼠标模式下画在地图下方的方向按钮
+const MOUSE_BUTTONS: [(Direction, &str); 4] = [
+    (Direction::Up, "[  ^  ]"),
+    (Direction::Down, "[  v  ]"),
+    (Direction::Left, "[  <  ]"),
+    (Direction::Right, "[  >  ]"),
+];
+/// 按钮行的缩进以及按钮之间的间距（列）
+const BUTTON_INDENT: usize = 2;
+const BUTTON_GAP: usize = 2;
+/// 重置所有颜色的 SGR 序列
+const SGR_RESET: &str = "\x1b[0m";
+
+/// 画面布局：负责终端坐标（列、行）与棋盘格子之间的换算
+/// 第 0 行是上边框，第 0 列是左边框；普通模式每个格子占两列（字符 + 间隔），
+/// 半格模式每个格子占一列、半行
+#[derive(Clone, Copy, Debug)]
+pub struct BoardLayout {
+    canvas_width: usize,
+    canvas_height: usize,
+    half_block: bool,
+}
+
+impl BoardLayout {
+    /// 终端坐标落在棋盘内时返回对应的格子
+    /// 半格模式下一个终端行对应上下两个格子，取上面那一个
+    pub fn screen_to_cell(&self, column: u16, row: u16) -> Option<Position> {
+        let (column, row) = (column as usize, row as usize);
+        if column == 0 || row == 0 {
+            return None;
+        }
+        let (x, y) = if self.half_block {
+            (column - 1, (row - 1) * 2)
+        } else {
+            ((column - 1) / 2, row - 1)
+        };
+        (x < self.canvas_width && y < self.canvas_height).then_some(Position {
+            x: x as i32,
+            y: y as i32,
+        })
+    }
+
+    /// 地图内部占用的终端行数
+    pub fn map_rows(&self) -> usize {
+        if self.half_block {
+            self.canvas_height.div_ceil(2)
+        } else {
+            self.canvas_height
+        }
+    }
+
+    /// 地图内部占用的终端列数
+    pub fn map_columns(&self) -> usize {
+        if self.half_block {
+            self.canvas_width
+        } else {
+            self.canvas_width * 2
+        }
+    }
+
+    /// 方向按钮所在的终端行：紧挨着下边框
+    pub fn button_row(&self) -> u16 {
+        (self.map_rows() + 2) as u16
+    }
+
+    /// 终端坐标落在某个方向按钮上时返回该方向
+    pub fn button_at(&self, column: u16, row: u16) -> Option<Direction> {
+        if row != self.button_row() {
+            return None;
+        }
+        let column = column as usize;
+        let mut start = BUTTON_INDENT;
+        for (direction, label) in MOUSE_BUTTONS {
+            let end = start + label.len();
+            if (start..end).contains(&column) {
+                return Some(direction);
+            }
+            start = end + BUTTON_GAP;
+        }
+        None
+    }
+}
+
+/// 边框闪烁信号：由事件订阅者触发，画布在接下来的若干帧里改用闪烁样式绘制边框
+/// 克隆出来的句柄共享同一个计数，重新开始游戏换了新画布也能继续使用；
+/// 计数是原子变量，带着它的画布仍然可以交给别的线程
+#[derive(Clone, Default, Debug)]
+pub struct BorderFlash(Arc<AtomicU32>);
+
+impl BorderFlash {
+    /// 让边框闪烁 frames 帧，与尚未闪完的帧数取较大值
+    pub fn trigger(&self, frames: u32) {
+        self.0.fetch_max(frames, Ordering::Relaxed);
+    }
+
+    /// 还剩多少帧没有闪完
+    pub fn remaining(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// 消耗一帧，返回这一帧是否应该以闪烁样式绘制
+    fn next_frame(&self) -> bool {
+        self.0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |remaining| {
+                remaining.checked_sub(1)
+            })
+            // 隔帧切换样式才有“闪”的效果
+            .is_ok_and(|remaining| remaining % 2 == 1)
+    }
+}
+
+/// 画布：把 Snake 与 GameState 组装成一帧文本，再交给 Renderer 输出
 pub struct Canvas {
     canvas_width: usize,
     canvas_height: usize,
     buffer: Vec<char>,
+    //是否在地图下方画出鼠标可点击的方向按钮
+    show_mouse_buttons: bool,
+    //边框闪烁信号
+    border_flash: BorderFlash,
+    //是否使用半格字符绘制地图
+    half_block: bool,
+    //成就进度，用于在面板上显示解锁提示
+    achievements: Option<Achievements>,
+}
+impl Default for Canvas {
+    fn default() -> Self {
+        Self::new()
+    }
 }
+
 impl Canvas {
+    /// 创建 40x15 的默认画布
     pub fn new() -> Self {
-        let canvas_width = 40;
-        let canvas_height = 15;
-
+        Self::with_size(40, 15, false)
+    }
+    /// 创建 80x30 的半格画布，占用的终端大小与默认画布相同
+    pub fn with_half_block() -> Self {
+        Self::with_size(80, 30, true)
+    }
+    fn with_size(canvas_width: usize, canvas_height: usize, half_block: bool) -> Self {
         Canvas {
             canvas_width,
             canvas_height,
             buffer: vec![' '; canvas_width * canvas_height],
+            show_mouse_buttons: false,
+            border_flash: BorderFlash::default(),
+            half_block,
+            achievements: None,
         }
     }
     pub fn clear(&mut self) {
@@ -38,29 +180,38 @@ impl Canvas {
             self.buffer[index] = display_char;
         }
     }
-    pub fn render_canvas(&mut self, snake: &snake::Snake, game_state: &mut GameState) {
-        let mut stdout = stdout();
+    /// 组装一帧并交给渲染后端输出
+    pub fn render_canvas(
+        &mut self,
+        renderer: &mut dyn Renderer,
+        snake: &snake::Snake,
+        game_state: &GameState,
+    ) -> Result<(), GameError> {
+        let frame = self.compose_frame(snake, game_state);
+        renderer.draw_frame(&frame)
+    }
 
+    /// 把当前游戏状态组装成一整帧文本，不涉及任何终端操作
+    pub fn compose_frame(&mut self, snake: &snake::Snake, game_state: &GameState) -> String {
         // 1. 数据准备：将逻辑状态同步到 Canvas 的 buffer
         self.prepare_buffer(snake);
 
-        // 2. 环境设置：将光标复位到 (0,0) 并隐藏
-        // 使用 queue! 暂存指令，最后一次性 flush
-        execute!(stdout, cursor::MoveTo(0, 0), cursor::Hide).unwrap();
-
-        // 3. 构造地图内容（保持一次性输出减少闪烁）
+        // 2. 构造地图内容（保持一次性输出减少闪烁）
         let mut frame = String::with_capacity(2048);
-        self.draw_map_to_string(&mut frame);
-
-        // 4. 计算分数并构造 UI 文本
-        let current_score = (snake.get_snake_body_length() as i32 - 3).max(0);
-        game_state.set_game_score(current_score as usize);
+        self.draw_map_to_string(&mut frame, game_state);
 
+        // 3. 构造鼠标按钮与 UI 文本
+        if self.show_mouse_buttons {
+            self.draw_buttons_to_string(&mut frame);
+        }
         self.draw_ui_to_string(&mut frame, game_state);
+        frame
+    }
 
-        // 5. 一次性打印，极致顺滑
-        print!("{}", frame);
-        stdout.flush().unwrap();
+    /// 只填充格子不拼接文本，按行优先顺序返回每个格子的字符，导出 GIF 等图像格式时使用
+    pub fn compose_cells(&mut self, snake: &snake::Snake) -> &[char] {
+        self.prepare_buffer(snake);
+        &self.buffer
     }
 
     /// 专门负责填充 Buffer 的内部逻辑
@@ -73,40 +224,135 @@ impl Canvas {
             self.set_canvas_disply_char(symbol, pos);
         }
 
+        // 写入障碍物
+        for pos in snake.get_obstacles() {
+            self.set_canvas_disply_char('#', &pos);
+        }
+
         // 写入食物
         self.set_canvas_disply_char('$', &snake.get_snak_food_position());
     }
 
     /// 专门负责构造地图边框和内容
-    fn draw_map_to_string(&self, frame: &mut String) {
-        let wall_h = "▄".repeat(self.canvas_width * 2);
-        let wall_f = "▀".repeat(self.canvas_width * 2);
+    fn draw_map_to_string(&self, frame: &mut String, state: &GameState) {
+        // 闪烁时整个边框换成阴影字符
+        let (corner, top, bottom, side) = if self.border_flash.next_frame() {
+            ('◇', '▒', '▒', '▒')
+        } else {
+            ('◆', '▄', '▀', '█')
+        };
+        let layout = self.layout();
+        let wall_h = top.to_string().repeat(layout.map_columns());
+        let wall_f = bottom.to_string().repeat(layout.map_columns());
 
         // 上边框
-        frame.push_str(&format!("◆{}◆\n", wall_h));
+        frame.push_str(&format!("{}{}{}\n", corner, wall_h, corner));
+
+        // 游戏结束时在地图正中间覆盖一行结局提示
+        let overlay_row = layout.map_rows() / 2;
+        let overlay = state.get_game_outcome().map(|outcome| {
+            let title = if outcome.is_win() {
+                "胜利"
+            } else {
+                "游戏结束"
+            };
+            format!(" {}：{} ", title, outcome.message())
+        });
 
-        for y in 0..self.canvas_height {
-            frame.push('█'); // 左边框
-            for x in 0..self.canvas_width {
-                frame.push(self.buffer[y * self.canvas_width + x]);
-                frame.push(' '); // 间隔，让显示更方正
+        for y in 0..layout.map_rows() {
+            if let Some(text) = overlay.as_deref()
+                && y == overlay_row
+            {
+                frame.push(side);
+                frame.push_str(&center_text(text, layout.map_columns()));
+                frame.push(side);
+                frame.push('\n');
+                continue;
             }
-            frame.push_str("█\n"); // 右边框
+            frame.push(side); // 左边框
+            if self.half_block {
+                self.draw_half_block_row(frame, y);
+            } else {
+                for x in 0..self.canvas_width {
+                    frame.push(self.buffer[y * self.canvas_width + x]);
+                    frame.push(' '); // 间隔，让显示更方正
+                }
+            }
+            frame.push(side); // 右边框
+            frame.push('\n');
         }
 
         // 下边框
-        frame.push_str(&format!("◆{}◆\n", wall_f));
+        frame.push_str(&format!("{}{}{}\n", corner, wall_f, corner));
+    }
+
+    /// 半格模式下的一个终端行：第 2y 行的格子画在上半格，第 2y+1 行画在下半格
+    /// 只在颜色变化时输出 SGR 序列，行尾恢复默认颜色，保证边框不被染色
+    fn draw_half_block_row(&self, frame: &mut String, y: usize) {
+        let color_at = |row: usize, x: usize| {
+            (row < self.canvas_height)
+                .then(|| cell_color(self.buffer[row * self.canvas_width + x]))
+                .flatten()
+        };
+        let mut current = (None, None);
+        for x in 0..self.canvas_width {
+            // 空的半格不上色，直接露出终端的默认背景
+            let (symbol, colors) = match (color_at(y * 2, x), color_at(y * 2 + 1, x)) {
+                (None, None) => (' ', (None, None)),
+                (Some(top), None) => ('▀', (Some(top), None)),
+                (None, Some(bottom)) => ('▄', (Some(bottom), None)),
+                (Some(top), Some(bottom)) => ('▀', (Some(top), Some(bottom))),
+            };
+            if colors != current {
+                frame.push_str(&sgr(colors));
+                current = colors;
+            }
+            frame.push(symbol);
+        }
+        if current != (None, None) {
+            frame.push_str(SGR_RESET);
+        }
+    }
+
+    /// 专门负责构造地图下方的方向按钮，位置与 BoardLayout::button_at 一致
+    fn draw_buttons_to_string(&self, frame: &mut String) {
+        frame.push_str(&" ".repeat(BUTTON_INDENT));
+        let labels: Vec<&str> = MOUSE_BUTTONS.iter().map(|(_, label)| *label).collect();
+        frame.push_str(&labels.join(&" ".repeat(BUTTON_GAP)));
+        frame.push('\n');
     }
 
     /// 专门负责构造下方的说明文字
     fn draw_ui_to_string(&self, frame: &mut String, state: &GameState) {
+        let mode = state.get_game_mode();
         frame.push_str("--------------------------------\n");
+        match mode.progress_text(state.get_game_tick()) {
+            Some(progress) => frame.push_str(&format!(
+                "游戏模式：{}  {}\n",
+                mode.display_name(),
+                progress
+            )),
+            None => frame.push_str(&format!("游戏模式：{}\n", mode.display_name())),
+        }
         frame.push_str(&format!(
             "历史最高分数：{}\n",
             state.get_game_historic_score()
         ));
-        frame.push_str(&format!("当前分数：{}\n", state.get_game_score()));
-        frame.push_str("操作说明: WSAD 移动, Q 退出!\n");
+        frame.push_str(&format!(
+            "当前分数：{}  等级：{}\n",
+            state.get_game_score(),
+            state.get_game_level()
+        ));
+        if let Some(achievement) = self.achievements.as_ref().and_then(|a| a.next_notice()) {
+            frame.push_str(&format!("成就解锁：{}！\n", achievement.title()));
+        }
+        if state.get_game_outcome().is_some() {
+            frame.push_str("操作说明: R 重新开始, Q 退出!\n");
+        } else if self.show_mouse_buttons {
+            frame.push_str("操作说明: 点击蛇头四周或下方按钮转向, Q 退出!\n");
+        } else {
+            frame.push_str("操作说明: WSAD 移动, Q 退出!\n");
+        }
         frame.push_str("---------------------------------\n");
     }
     pub fn get_canvas_width(&self) -> usize {
@@ -121,4 +367,370 @@ impl Canvas {
     // pub fn set_canvas_height(&mut self, new_height: usize) {
     //     self.canvas_height = new_height;
     // }
+    /// 使用共享的边框闪烁信号，通常来自事件订阅者
+    pub fn set_border_flash(&mut self, border_flash: BorderFlash) {
+        self.border_flash = border_flash;
+    }
+    /// 使用共享的成就进度，有成就解锁时在面板上提示
+    pub fn set_achievements(&mut self, achievements: Achievements) {
+        self.achievements = Some(achievements);
+    }
+    /// 是否在地图下方画出鼠标方向按钮
+    pub fn set_show_mouse_buttons(&mut self, show: bool) {
+        self.show_mouse_buttons = show;
+    }
+    /// 当前画面的布局，用于把鼠标坐标换算成格子
+    pub fn layout(&self) -> BoardLayout {
+        BoardLayout {
+            canvas_width: self.canvas_width,
+            canvas_height: self.canvas_height,
+            half_block: self.half_block,
+        }
+    }
+}
+
+/// 半格模式下每种格子的前景色（SGR 颜色代码），空格子返回 None
+fn cell_color(symbol: char) -> Option<u8> {
+    match symbol {
+        'O' => Some(92), // 蛇头：亮绿色
+        '■' => Some(32), // 蛇身：绿色
+        '$' => Some(91), // 食物：亮红色
+        '#' => Some(37), // 障碍物：灰白色
+        _ => None,
+    }
+}
+
+/// 生成设置前景色/背景色的 SGR 序列，背景色代码比前景色大 10
+fn sgr((foreground, background): (Option<u8>, Option<u8>)) -> String {
+    let mut codes = String::from("\x1b[0");
+    for code in foreground.into_iter().chain(background.map(|c| c + 10)) {
+        codes.push_str(&format!(";{}", code));
+    }
+    codes.push('m');
+    codes
+}
+
+/// 把文字居中填充到指定的显示宽度，中文等全角字符按两列计算
+fn center_text(text: &str, width: usize) -> String {
+    let text_width: usize = text.chars().map(char_width).sum();
+    let padding = width.saturating_sub(text_width);
+    let left = padding / 2;
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(padding - left))
+}
+
+fn char_width(c: char) -> usize {
+    if c.is_ascii() { 1 } else { 2 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game_event::{EventSubscriber, GameEvent};
+    use crate::game::game_mode::{GameMode, GameOutcome};
+    use crate::game::game_renderer::MemoryRenderer;
+    use crate::game::snake::Snake;
+    use std::{env, fs, path::PathBuf};
+
+    /// 把一帧渲染到内存后端并取出文本
+    fn render(snake: &Snake, state: &GameState) -> String {
+        let mut canvas = Canvas::new();
+        let mut renderer = MemoryRenderer::new();
+        canvas.render_canvas(&mut renderer, snake, state).unwrap();
+        renderer.snapshot().to_string()
+    }
+
+    /// 与 src/game/snapshots 下的快照比较
+    /// 设置环境变量 UPDATE_SNAPSHOTS=1 运行测试即可重新生成快照
+    fn assert_snapshot(name: &str, frame: &str) {
+        let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "src/game/snapshots", name]
+            .iter()
+            .collect::<PathBuf>()
+            .with_extension("txt");
+
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, frame).unwrap();
+            return;
+        }
+
+        let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+            panic!(
+                "读取快照 {} 失败: {}，可以用 UPDATE_SNAPSHOTS=1 生成",
+                path.display(),
+                e
+            )
+        });
+        assert!(
+            expected == frame,
+            "快照 {} 不一致\n--- 期望 ---\n{}\n--- 实际 ---\n{}",
+            name,
+            expected,
+            frame
+        );
+    }
+
+    fn pos(x: i32, y: i32) -> Position {
+        Position { x, y }
+    }
+
+    #[test]
+    fn initial_board() {
+        let state = GameState::with_historic_score(GameMode::Endless, 36);
+        assert_snapshot(
+            "initial_board",
+            &render(&Snake::new(40, 15).unwrap(), &state),
+        );
+    }
+
+    #[test]
+    fn bent_body_and_food() {
+        let snake = Snake::from_body(
+            [pos(10, 3), pos(10, 4), pos(10, 5), pos(11, 5), pos(12, 5)],
+            Direction::Up,
+            pos(20, 7),
+            40,
+            15,
+        )
+        .unwrap();
+        let mut state = GameState::with_historic_score(GameMode::Endless, 36);
+        state.set_game_score(2);
+        assert_snapshot("bent_body_and_food", &render(&snake, &state));
+    }
+
+    #[test]
+    fn snake_and_food_on_borders() {
+        // 蛇头与食物分别贴着左上角和右下角，检查边框不会被覆盖
+        let snake = Snake::from_body(
+            [pos(0, 0), pos(0, 1), pos(0, 2)],
+            Direction::Up,
+            pos(39, 14),
+            40,
+            15,
+        )
+        .unwrap();
+        let state = GameState::with_historic_score(GameMode::Endless, 0);
+        assert_snapshot("snake_and_food_on_borders", &render(&snake, &state));
+    }
+
+    #[test]
+    fn time_attack_panel() {
+        let mut state = GameState::with_historic_score(GameMode::TimeAttack { seconds: 60 }, 12);
+        state.game_tick = 40;
+        state.set_game_score(3);
+        assert_snapshot(
+            "time_attack_panel",
+            &render(&Snake::new(40, 15).unwrap(), &state),
+        );
+    }
+
+    #[test]
+    fn survival_panel() {
+        let mut state = GameState::with_historic_score(GameMode::Survival { ticks: 600 }, 5);
+        state.game_tick = 123;
+        assert_snapshot(
+            "survival_panel",
+            &render(&Snake::new(40, 15).unwrap(), &state),
+        );
+    }
+
+    #[test]
+    fn daily_board_with_obstacles() {
+        let date = crate::game::daily::ChallengeDate::parse("2026-10-18").unwrap();
+        let state = GameState::with_historic_score(GameMode::Daily { date }, 0);
+        let mut snake = Snake::new(40, 15).unwrap();
+        let body: Vec<_> = snake.get_snake_body().iter().copied().collect();
+        snake.place_obstacles(crate::game::daily::obstacle_layout(
+            date.seed(),
+            40,
+            15,
+            &body,
+            Direction::Right.to_vec(),
+        ));
+        assert_snapshot("daily_board", &render(&snake, &state));
+    }
+
+    #[test]
+    fn game_over_overlay() {
+        let mut state = GameState::with_historic_score(GameMode::Endless, 36);
+        state.set_game_score(7);
+        state.set_game_outcome(GameOutcome::HitWall);
+        assert_snapshot(
+            "game_over_overlay",
+            &render(&Snake::new(40, 15).unwrap(), &state),
+        );
+    }
+
+    #[test]
+    fn win_overlay() {
+        let mut state = GameState::with_historic_score(GameMode::TimeAttack { seconds: 60 }, 0);
+        state.game_tick = 240;
+        state.set_game_score(9);
+        state.set_game_outcome(GameOutcome::TimeUp);
+        assert_snapshot("win_overlay", &render(&Snake::new(40, 15).unwrap(), &state));
+    }
+
+    #[test]
+    fn mouse_buttons_under_board() {
+        let mut canvas = Canvas::new();
+        canvas.set_show_mouse_buttons(true);
+        let mut renderer = MemoryRenderer::new();
+        let state = GameState::with_historic_score(GameMode::Endless, 0);
+        canvas
+            .render_canvas(&mut renderer, &Snake::new(40, 15).unwrap(), &state)
+            .unwrap();
+        let frame = renderer.snapshot();
+        assert_snapshot("mouse_buttons", frame);
+
+        // 每个按钮标签的中间一列都应该能点中对应的方向
+        let layout = canvas.layout();
+        let button_line = frame.lines().nth(layout.button_row() as usize).unwrap();
+        for (direction, label) in MOUSE_BUTTONS {
+            let column = button_line.find(label).unwrap() + label.len() / 2;
+            assert_eq!(
+                layout.button_at(column as u16, layout.button_row()),
+                Some(direction)
+            );
+        }
+        assert_eq!(layout.button_at(0, layout.button_row()), None);
+        assert_eq!(layout.button_at(5, layout.button_row() + 1), None);
+    }
+
+    #[test]
+    fn screen_coordinates_map_to_cells() {
+        let layout = Canvas::new().layout();
+        // 格子 (0,0) 占据第 1、2 列，第 1 行
+        assert_eq!(layout.screen_to_cell(1, 1), Some(pos(0, 0)));
+        assert_eq!(layout.screen_to_cell(2, 1), Some(pos(0, 0)));
+        assert_eq!(layout.screen_to_cell(3, 1), Some(pos(1, 0)));
+        assert_eq!(layout.screen_to_cell(80, 15), Some(pos(39, 14)));
+        // 边框与地图外
+        assert_eq!(layout.screen_to_cell(0, 5), None);
+        assert_eq!(layout.screen_to_cell(5, 0), None);
+        assert_eq!(layout.screen_to_cell(81, 5), None);
+        assert_eq!(layout.screen_to_cell(5, 16), None);
+    }
+
+    #[test]
+    fn flashing_border() {
+        let flash = BorderFlash::default();
+        let mut canvas = Canvas::new();
+        canvas.set_border_flash(flash.clone());
+        let state = GameState::with_historic_score(GameMode::Endless, 0);
+        let snake = Snake::new(40, 15).unwrap();
+        let mut renderer = MemoryRenderer::new();
+
+        flash.trigger(2);
+        // 闪烁两帧：第一帧正常、第二帧阴影边框，之后恢复
+        let mut frames = Vec::new();
+        for _ in 0..3 {
+            canvas.render_canvas(&mut renderer, &snake, &state).unwrap();
+            frames.push(renderer.snapshot().to_string());
+        }
+        assert_eq!(frames[0], frames[2]);
+        assert!(frames[0].starts_with('◆'));
+        assert_snapshot("flashing_border", &frames[1]);
+    }
+
+    #[test]
+    fn achievement_notice_in_panel() {
+        let achievements = Achievements::in_memory();
+        let mut canvas = Canvas::new();
+        canvas.set_achievements(achievements.clone());
+        let mut state = GameState::with_historic_score(GameMode::Endless, 0);
+        state.set_game_score(10);
+        let snake = Snake::new(40, 15).unwrap();
+        assert!(!canvas.compose_frame(&snake, &state).contains("成就解锁"));
+
+        let mut subscriber = achievements.clone();
+        let event = GameEvent::FoodEaten {
+            score: 10,
+            length: 13,
+            position: pos(5, 8),
+        };
+        subscriber.on_event(20, &event).unwrap();
+        assert!(
+            canvas
+                .compose_frame(&snake, &state)
+                .contains("当前分数：10  等级：3\n成就解锁：初出茅庐！\n")
+        );
+    }
+
+    #[test]
+    fn canvas_with_subscribers_moves_to_another_thread() {
+        let flash = BorderFlash::default();
+        let mut canvas = Canvas::new();
+        canvas.set_border_flash(flash.clone());
+        canvas.set_achievements(Achievements::in_memory());
+        flash.trigger(2);
+
+        let snake = Snake::new(40, 15).unwrap();
+        let state = GameState::with_historic_score(GameMode::Endless, 0);
+        let frame = std::thread::spawn(move || canvas.compose_frame(&snake, &state))
+            .join()
+            .unwrap();
+        assert!(frame.contains("当前分数"));
+        assert_eq!(flash.remaining(), 1);
+    }
+
+    /// 去掉 SGR 颜色序列，只保留字符布局
+    fn strip_ansi(frame: &str) -> String {
+        let mut plain = String::with_capacity(frame.len());
+        let mut chars = frame.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|c| *c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    #[test]
+    fn half_block_board() {
+        // 蛇头在第 4 行（上半格），蛇身有一节在第 5 行（同一终端行的下半格），食物单独占第 7 行的下半格
+        let snake = Snake::from_body(
+            [pos(3, 4), pos(2, 4), pos(2, 5), pos(1, 5)],
+            Direction::Right,
+            pos(10, 7),
+            80,
+            30,
+        )
+        .unwrap();
+        let state = GameState::with_historic_score(GameMode::Endless, 0);
+        let mut canvas = Canvas::with_half_block();
+        let frame = canvas.compose_frame(&snake, &state);
+        assert_snapshot("half_block_board", &strip_ansi(&frame));
+
+        // 地图占 15 个终端行，和默认画布一样高
+        let lines: Vec<&str> = frame.lines().collect();
+        assert!(lines[16].starts_with('◆'));
+        // 终端第 3 行：两格蛇身上下叠在一起，蛇头只占上半格，行尾恢复默认颜色
+        assert_eq!(
+            lines[3],
+            format!(
+                "█ \x1b[0;32m▄\x1b[0;32;42m▀\x1b[0;92m▀\x1b[0m{}█",
+                " ".repeat(76)
+            )
+        );
+        assert!(lines[4].contains("\x1b[0;91m▄\x1b[0m"));
+    }
+
+    #[test]
+    fn half_block_layout_maps_two_rows_per_line() {
+        let mut canvas = Canvas::with_half_block();
+        canvas.set_show_mouse_buttons(true);
+        let layout = canvas.layout();
+        assert_eq!(layout.screen_to_cell(1, 1), Some(pos(0, 0)));
+        assert_eq!(layout.screen_to_cell(80, 15), Some(pos(79, 28)));
+        assert_eq!(layout.screen_to_cell(81, 5), None);
+        assert_eq!(layout.screen_to_cell(5, 16), None);
+        assert_eq!(layout.button_row(), 17);
+
+        let snake = Snake::new(80, 30).unwrap();
+        let state = GameState::with_historic_score(GameMode::Endless, 0);
+        let frame = canvas.compose_frame(&snake, &state);
+        let button_line = frame.lines().nth(layout.button_row() as usize).unwrap();
+        assert!(button_line.starts_with("  [  ^  ]"));
+    }
 }
@@ -0,0 +1,65 @@
+/*
+在这个文件里面定义游戏运行过程中可能出现的错误
+终端输出失败（例如管道被关闭）或存档写入失败时不再 panic，而是逐层向上返回
+*/
+use std::{error, fmt, io};
+
+/// 游戏错误枚举定义
+#[derive(Debug)]
+pub enum GameError {
+    /// 终端渲染或输出失败
+    Terminal(io::Error),
+    /// 保存游戏数据失败
+    SaveData(io::Error),
+    /// 输入脚本无法读取或格式错误
+    Script(String),
+    /// 写入事件日志失败
+    Log(io::Error),
+    /// 导出回放失败
+    Export(io::Error),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::Terminal(e) => write!(f, "终端输出失败: {}", e),
+            GameError::SaveData(e) => write!(f, "保存游戏数据失败: {}", e),
+            GameError::Script(msg) => write!(f, "输入脚本错误: {}", msg),
+            GameError::Log(e) => write!(f, "写入日志失败: {}", e),
+            GameError::Export(e) => write!(f, "导出回放失败: {}", e),
+        }
+    }
+}
+
+impl error::Error for GameError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            GameError::Terminal(e)
+            | GameError::SaveData(e)
+            | GameError::Log(e)
+            | GameError::Export(e) => Some(e),
+            GameError::Script(_) => None,
+        }
+    }
+}
+
+// 渲染时的 `?` 默认把 io::Error 视为终端错误
+impl From<io::Error> for GameError {
+    fn from(e: io::Error) -> Self {
+        GameError::Terminal(e)
+    }
+}
+
+// main 返回 io::Result，保留原始错误类型的同时附带中文描述
+impl From<GameError> for io::Error {
+    fn from(e: GameError) -> Self {
+        let kind = match &e {
+            GameError::Terminal(source)
+            | GameError::SaveData(source)
+            | GameError::Log(source)
+            | GameError::Export(source) => source.kind(),
+            GameError::Script(_) => io::ErrorKind::InvalidInput,
+        };
+        io::Error::new(kind, e)
+    }
+}
@@ -0,0 +1,378 @@
+/*
+在这个文件里面定义游戏事件流以及事件订阅者
+模拟过程中（吃到食物、升级、死亡、破纪录）产生的事件先缓存在 GameState 里，
+每个 tick 结束后由 EventBus 依次分发给订阅者：
+TerminalFeedback：响铃或让边框闪烁，给玩家即时反馈
+EventLogger：把事件以 JSON Lines 格式追加到文件，方便事后分析
+*/
+use crate::game::game_direction::Direction;
+use crate::game::game_display::BorderFlash;
+use crate::game::game_error::GameError;
+use crate::game::game_mode::GameOutcome;
+use crate::game::snake::Position;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// 游戏事件
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameEvent {
+    /// 吃到食物，score 与 length 为吃完后的分数和蛇长
+    FoodEaten {
+        score: usize,
+        length: usize,
+        position: Position,
+    },
+    /// 蛇头转向
+    Turned { from: Direction, to: Direction },
+    /// 没吃食物也长了一节（生存挑战的自动增长），length 为增长后的蛇长
+    Grew { length: usize },
+    /// 等级提升
+    LevelUp { level: usize },
+    /// 本局第一次突破历史最高分，previous 为原来的最高分（没有记录时为 0）
+    NewHighScore { score: usize, previous: usize },
+    /// 撞墙或撞到自己
+    Died { outcome: GameOutcome },
+    /// 完成了模式目标（时间到、存活到最后、填满棋盘）
+    Won { outcome: GameOutcome },
+}
+
+impl GameEvent {
+    /// 事件的英文标识
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameEvent::FoodEaten { .. } => "food_eaten",
+            GameEvent::Turned { .. } => "turned",
+            GameEvent::Grew { .. } => "grew",
+            GameEvent::LevelUp { .. } => "level_up",
+            GameEvent::NewHighScore { .. } => "new_high_score",
+            GameEvent::Died { .. } => "died",
+            GameEvent::Won { .. } => "won",
+        }
+    }
+
+    /// 转换成一行 JSON，附带事件发生的 tick
+    pub fn to_json(&self, tick: u64) -> String {
+        let fields = match self {
+            GameEvent::FoodEaten {
+                score,
+                length,
+                position,
+            } => {
+                format!(
+                    ",\"score\":{},\"length\":{},\"x\":{},\"y\":{}",
+                    score, length, position.x, position.y
+                )
+            }
+            GameEvent::Turned { from, to } => {
+                format!(",\"from\":\"{}\",\"to\":\"{}\"", from.name(), to.name())
+            }
+            GameEvent::Grew { length } => format!(",\"length\":{}", length),
+            GameEvent::LevelUp { level } => format!(",\"level\":{}", level),
+            GameEvent::NewHighScore { score, previous } => {
+                format!(",\"score\":{},\"previous\":{}", score, previous)
+            }
+            GameEvent::Died { outcome } | GameEvent::Won { outcome } => {
+                format!(",\"outcome\":\"{}\"", outcome.id())
+            }
+        };
+        format!(
+            "{{\"tick\":{},\"event\":\"{}\"{}}}",
+            tick,
+            self.name(),
+            fields
+        )
+    }
+}
+
+/// 事件订阅者特征定义
+pub trait EventSubscriber {
+    fn on_event(&mut self, tick: u64, event: &GameEvent) -> Result<(), GameError>;
+}
+
+/// 事件总线：按订阅顺序把事件分发给每个订阅者
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Box<dyn EventSubscriber>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&mut self, subscriber: Box<dyn EventSubscriber>) {
+        self.subscribers.push(subscriber);
+    }
+
+    pub fn publish(&mut self, tick: u64, events: &[GameEvent]) -> Result<(), GameError> {
+        for event in events {
+            for subscriber in self.subscribers.iter_mut() {
+                subscriber.on_event(tick, event)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 终端反馈方式
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FeedbackStyle {
+    /// 终端响铃（\x07）
+    Bell,
+    /// 边框闪烁
+    Flash,
+}
+
+/// 终端反馈订阅者：吃到食物轻提示一下，升级、破纪录和游戏结束时提示得更明显
+pub struct TerminalFeedback<W: Write> {
+    style: FeedbackStyle,
+    out: W,
+    flash: BorderFlash,
+}
+
+impl TerminalFeedback<io::Stdout> {
+    /// 响铃写到标准输出，闪烁通过 flash 通知画布
+    pub fn new(style: FeedbackStyle, flash: BorderFlash) -> Self {
+        Self::with_writer(style, flash, io::stdout())
+    }
+}
+
+impl<W: Write> TerminalFeedback<W> {
+    const BELL: &'static [u8] = b"\x07";
+
+    pub fn with_writer(style: FeedbackStyle, flash: BorderFlash, out: W) -> Self {
+        TerminalFeedback { style, out, flash }
+    }
+}
+
+impl<W: Write> EventSubscriber for TerminalFeedback<W> {
+    fn on_event(&mut self, _tick: u64, event: &GameEvent) -> Result<(), GameError> {
+        // 转向和自动增长太频繁，不做提示
+        if let GameEvent::Turned { .. } | GameEvent::Grew { .. } = event {
+            return Ok(());
+        }
+        match self.style {
+            FeedbackStyle::Bell => {
+                // 连续的响铃在大多数终端里会合并成一声，每个事件响一次即可
+                self.out.write_all(Self::BELL)?;
+                self.out.flush()?;
+            }
+            FeedbackStyle::Flash => {
+                // 越重要的事件闪烁的帧数越多
+                let frames = match event {
+                    GameEvent::FoodEaten { .. }
+                    | GameEvent::Turned { .. }
+                    | GameEvent::Grew { .. } => 1,
+                    GameEvent::LevelUp { .. } | GameEvent::NewHighScore { .. } => 4,
+                    GameEvent::Died { .. } | GameEvent::Won { .. } => 8,
+                };
+                self.flash.trigger(frames);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 事件日志订阅者：每个事件一行 JSON
+pub struct EventLogger<W: Write> {
+    out: W,
+}
+
+impl EventLogger<BufWriter<File>> {
+    /// 以追加方式打开日志文件，多局游戏的事件会写进同一个文件
+    pub fn open(path: &Path) -> Result<Self, GameError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(GameError::Log)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> EventLogger<W> {
+    pub fn new(out: W) -> Self {
+        EventLogger { out }
+    }
+}
+
+impl<W: Write> EventSubscriber for EventLogger<W> {
+    fn on_event(&mut self, tick: u64, event: &GameEvent) -> Result<(), GameError> {
+        writeln!(self.out, "{}", event.to_json(tick)).map_err(GameError::Log)?;
+        // 游戏结束的事件写完立即落盘，防止强制退出时丢失
+        if matches!(event, GameEvent::Died { .. } | GameEvent::Won { .. }) {
+            self.out.flush().map_err(GameError::Log)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game_mode::GameMode;
+    use crate::game::snake::Snake;
+    use crate::game::{GameState, step};
+
+    fn collect_events(snake: &mut Snake, state: &mut GameState, ticks: usize) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        for _ in 0..ticks {
+            step(snake, state);
+            events.extend(state.drain_events());
+        }
+        events
+    }
+
+    #[test]
+    fn simulation_emits_events() {
+        // 1x4 的棋盘：吃完两个食物正好填满棋盘
+        let mut snake = Snake::from_body(
+            [Position { x: 1, y: 0 }, Position { x: 0, y: 0 }],
+            Direction::Right,
+            Position { x: 2, y: 0 },
+            4,
+            1,
+        )
+        .unwrap();
+        let mut state = GameState::with_historic_score(GameMode::Endless, 0);
+        let events = collect_events(&mut snake, &mut state, 2);
+        assert_eq!(
+            events,
+            [
+                GameEvent::FoodEaten {
+                    score: 1,
+                    length: 3,
+                    position: Position { x: 2, y: 0 }
+                },
+                GameEvent::NewHighScore {
+                    score: 1,
+                    previous: 0
+                },
+                GameEvent::FoodEaten {
+                    score: 2,
+                    length: 4,
+                    position: Position { x: 3, y: 0 }
+                },
+                GameEvent::Won {
+                    outcome: GameOutcome::BoardFull
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn level_up_and_high_score_are_announced_once() {
+        let mut snake = Snake::from_body(
+            [Position { x: 1, y: 0 }, Position { x: 0, y: 0 }],
+            Direction::Right,
+            Position { x: 2, y: 0 },
+            10,
+            1,
+        )
+        .unwrap();
+        let mut state = GameState::with_historic_score(GameMode::Endless, 3);
+        state.set_game_score(4);
+        let events = collect_events(&mut snake, &mut state, 1);
+        let names: Vec<&str> = events.iter().map(GameEvent::name).collect();
+        assert_eq!(names, ["food_eaten", "level_up", "new_high_score"]);
+        assert_eq!(events[1], GameEvent::LevelUp { level: 2 });
+
+        // 再吃一个食物只会有 food_eaten
+        snake = Snake::from_body(
+            [Position { x: 1, y: 0 }, Position { x: 0, y: 0 }],
+            Direction::Right,
+            Position { x: 2, y: 0 },
+            10,
+            1,
+        )
+        .unwrap();
+        let events = collect_events(&mut snake, &mut state, 1);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name(), "food_eaten");
+    }
+
+    #[test]
+    fn running_into_the_wall_emits_died() {
+        let mut snake = Snake::from_body(
+            [Position { x: 0, y: 0 }],
+            Direction::Left,
+            Position { x: 1, y: 1 },
+            2,
+            2,
+        )
+        .unwrap();
+        let mut state = GameState::with_historic_score(GameMode::Endless, 0);
+        step(&mut snake, &mut state);
+        assert_eq!(
+            state.drain_events(),
+            [GameEvent::Died {
+                outcome: GameOutcome::HitWall
+            }]
+        );
+        assert!(state.drain_events().is_empty());
+    }
+
+    #[test]
+    fn survival_growth_emits_grew() {
+        let mut snake = Snake::from_body(
+            [Position { x: 1, y: 0 }, Position { x: 0, y: 0 }],
+            Direction::Right,
+            Position { x: 39, y: 0 },
+            40,
+            1,
+        )
+        .unwrap();
+        let mut state = GameState::with_historic_score(GameMode::Survival { ticks: 300 }, 0);
+        // 第 20 个 tick 之后的那一步长出一节
+        assert!(collect_events(&mut snake, &mut state, 20).is_empty());
+        let events = collect_events(&mut snake, &mut state, 1);
+        assert_eq!(events, [GameEvent::Grew { length: 3 }]);
+        assert_eq!(snake.get_snake_body_length(), 3);
+    }
+
+    #[test]
+    fn logger_writes_json_lines() {
+        let mut logger = EventLogger::new(Vec::new());
+        let events = [
+            GameEvent::FoodEaten {
+                score: 1,
+                length: 4,
+                position: Position { x: 3, y: 4 },
+            },
+            GameEvent::Turned {
+                from: Direction::Up,
+                to: Direction::Left,
+            },
+            GameEvent::Died {
+                outcome: GameOutcome::HitSelf,
+            },
+        ];
+        for event in &events {
+            logger.on_event(7, event).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(logger.out).unwrap(),
+            "{\"tick\":7,\"event\":\"food_eaten\",\"score\":1,\"length\":4,\"x\":3,\"y\":4}\n\
+             {\"tick\":7,\"event\":\"turned\",\"from\":\"up\",\"to\":\"left\"}\n\
+             {\"tick\":7,\"event\":\"died\",\"outcome\":\"hit_self\"}\n"
+        );
+    }
+
+    #[test]
+    fn feedback_rings_bell_or_flashes_border() {
+        let event = GameEvent::LevelUp { level: 2 };
+
+        let flash = BorderFlash::default();
+        let mut bell =
+            TerminalFeedback::with_writer(FeedbackStyle::Bell, flash.clone(), Vec::new());
+        bell.on_event(1, &event).unwrap();
+        assert_eq!(bell.out, b"\x07");
+
+        let mut flasher =
+            TerminalFeedback::with_writer(FeedbackStyle::Flash, flash.clone(), Vec::new());
+        flasher.on_event(1, &event).unwrap();
+        assert!(flasher.out.is_empty());
+        assert_eq!(flash.remaining(), 4);
+    }
+}
@@ -0,0 +1,230 @@
+/*
+在这个文件里面定义游戏模式以及每种模式的胜负条件
+无尽模式：只有撞墙或撞到自己才会结束
+限时挑战：在 N 秒内尽可能多地得分，时间耗尽即完成挑战
+生存挑战：蛇会定时自动变长，坚持 N 个 tick 不死即获胜
+每日挑战：与无尽模式规则相同，但棋盘上有障碍物，食物和障碍物都由当天的日期决定
+*/
+use crate::game::TICK_MILLIS;
+use crate::game::daily::ChallengeDate;
+
+/// 生存挑战中蛇自动增长一节的间隔（tick）
+const SURVIVAL_GROW_INTERVAL: u64 = 20;
+
+/// 游戏模式枚举定义
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameMode {
+    /// 无尽模式
+    Endless,
+    /// 限时挑战，seconds 为挑战时长（秒）
+    TimeAttack { seconds: u64 },
+    /// 生存挑战，ticks 为需要坚持的 tick 数
+    Survival { ticks: u64 },
+    /// 每日挑战，date 决定当天的棋盘
+    Daily { date: ChallengeDate },
+}
+
+/// 游戏结局枚举定义
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameOutcome {
+    /// 蛇出界
+    HitWall,
+    /// 蛇与自身碰撞
+    HitSelf,
+    /// 蛇撞上障碍物
+    HitObstacle,
+    /// 限时挑战时间耗尽
+    TimeUp,
+    /// 生存挑战坚持到了最后
+    Survived,
+    /// 蛇填满了整个棋盘
+    BoardFull,
+}
+
+impl GameMode {
+    pub const DEFAULT_TIME_ATTACK_SECONDS: u64 = 60;
+    pub const DEFAULT_SURVIVAL_TICKS: u64 = 600;
+
+    /// 根据模式名称和可选的限制值构造模式，每日挑战默认为今天；
+    /// 无尽模式和每日挑战没有限制值，给了 limit 时返回错误
+    pub fn from_name(name: &str, limit: Option<u64>) -> Result<GameMode, String> {
+        match name {
+            "endless" | "daily" if limit.is_some() => Err(format!(
+                "{} 模式没有时间或 tick 限制，不能指定 --limit",
+                name
+            )),
+            "endless" => Ok(GameMode::Endless),
+            "time-attack" => Ok(GameMode::TimeAttack {
+                seconds: limit.unwrap_or(Self::DEFAULT_TIME_ATTACK_SECONDS),
+            }),
+            "survival" => Ok(GameMode::Survival {
+                ticks: limit.unwrap_or(Self::DEFAULT_SURVIVAL_TICKS),
+            }),
+            "daily" => Ok(GameMode::Daily {
+                date: ChallengeDate::today(),
+            }),
+            other => Err(format!("未知的游戏模式：{}", other)),
+        }
+    }
+
+    /// 模式的命令行名称，与 from_name 互为逆操作
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Endless => "endless",
+            GameMode::TimeAttack { .. } => "time-attack",
+            GameMode::Survival { .. } => "survival",
+            GameMode::Daily { .. } => "daily",
+        }
+    }
+
+    /// 模式的显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            GameMode::Endless => "无尽模式",
+            GameMode::TimeAttack { .. } => "限时挑战",
+            GameMode::Survival { .. } => "生存挑战",
+            GameMode::Daily { .. } => "每日挑战",
+        }
+    }
+
+    /// 每个模式（含限制值）在存档中拥有独立的排行榜分类
+    /// 无尽模式沿用旧的键名，保证已有的历史最高分不会丢失
+    pub fn score_key(&self) -> String {
+        match self {
+            GameMode::Endless => "historic_score".to_string(),
+            GameMode::TimeAttack { seconds } => format!("historic_score_time_attack_{}s", seconds),
+            GameMode::Survival { ticks } => format!("historic_score_survival_{}t", ticks),
+            GameMode::Daily { date } => format!("historic_score_daily_{}", date),
+        }
+    }
+
+    /// 检查当前 tick 是否满足模式的结束条件
+    pub fn check_finished(&self, tick: u64) -> Option<GameOutcome> {
+        match *self {
+            GameMode::Endless | GameMode::Daily { .. } => None,
+            GameMode::TimeAttack { seconds } => {
+                // 很大的 --limit 不能溢出，否则限时挑战会立刻结束
+                let limit_ticks = seconds.saturating_mul(1000) / TICK_MILLIS;
+                (tick >= limit_ticks).then_some(GameOutcome::TimeUp)
+            }
+            GameMode::Survival { ticks } => (tick >= ticks).then_some(GameOutcome::Survived),
+        }
+    }
+
+    /// 生存挑战中每隔固定 tick 自动增长
+    pub fn should_auto_grow(&self, tick: u64) -> bool {
+        matches!(self, GameMode::Survival { .. })
+            && tick > 0
+            && tick.is_multiple_of(SURVIVAL_GROW_INTERVAL)
+    }
+
+    /// 面板上显示的模式进度，例如倒计时或坚持的 tick 数
+    pub fn progress_text(&self, tick: u64) -> Option<String> {
+        match *self {
+            GameMode::Endless => None,
+            GameMode::TimeAttack { seconds } => {
+                let remaining_millis = seconds
+                    .saturating_mul(1000)
+                    .saturating_sub(tick.saturating_mul(TICK_MILLIS));
+                // 向上取整，避免还剩几百毫秒时就显示 0 秒
+                Some(format!("剩余时间：{} 秒", remaining_millis.div_ceil(1000)))
+            }
+            GameMode::Survival { ticks } => Some(format!("坚持：{}/{}", tick.min(ticks), ticks)),
+            GameMode::Daily { date } => Some(format!("日期：{}", date)),
+        }
+    }
+
+    /// 限时挑战的秒数或生存挑战的 tick 数，与 from_name 的 limit 参数对应
+    pub fn limit(&self) -> Option<u64> {
+        match *self {
+            GameMode::TimeAttack { seconds } => Some(seconds),
+            GameMode::Survival { ticks } => Some(ticks),
+            GameMode::Endless | GameMode::Daily { .. } => None,
+        }
+    }
+
+    /// 每日挑战使用当天的随机种子，其他模式返回 None
+    pub fn daily_seed(&self) -> Option<u64> {
+        match self {
+            GameMode::Daily { date } => Some(date.seed()),
+            _ => None,
+        }
+    }
+}
+
+impl GameOutcome {
+    pub fn is_win(&self) -> bool {
+        matches!(
+            self,
+            GameOutcome::TimeUp | GameOutcome::Survived | GameOutcome::BoardFull
+        )
+    }
+
+    /// 所有结局
+    pub const ALL: [GameOutcome; 6] = [
+        GameOutcome::HitWall,
+        GameOutcome::HitSelf,
+        GameOutcome::HitObstacle,
+        GameOutcome::TimeUp,
+        GameOutcome::Survived,
+        GameOutcome::BoardFull,
+    ];
+
+    /// 根据英文标识查找结局，与 id 互为逆操作
+    pub fn from_id(id: &str) -> Option<GameOutcome> {
+        Self::ALL.into_iter().find(|o| o.id() == id)
+    }
+
+    /// 结局的英文标识，用于导出 JSON 等机器可读的格式
+    pub fn id(&self) -> &'static str {
+        match self {
+            GameOutcome::HitWall => "hit_wall",
+            GameOutcome::HitSelf => "hit_self",
+            GameOutcome::HitObstacle => "hit_obstacle",
+            GameOutcome::TimeUp => "time_up",
+            GameOutcome::Survived => "survived",
+            GameOutcome::BoardFull => "board_full",
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            GameOutcome::HitWall => "您操控的蛇出界！",
+            GameOutcome::HitSelf => "您操控的蛇与自身碰撞！",
+            GameOutcome::HitObstacle => "您操控的蛇撞上了障碍物！",
+            GameOutcome::TimeUp => "时间到！挑战完成！",
+            GameOutcome::Survived => "恭喜！您成功存活到了最后！",
+            GameOutcome::BoardFull => "蛇填满了整个棋盘！完美通关！",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn huge_time_limit_does_not_overflow() {
+        let mode = GameMode::TimeAttack { seconds: u64::MAX };
+        assert_eq!(mode.check_finished(1_000), None);
+        assert!(mode.progress_text(1_000).is_some());
+
+        let mode = GameMode::TimeAttack { seconds: 1 };
+        assert_eq!(
+            mode.check_finished(1_000 / TICK_MILLIS),
+            Some(GameOutcome::TimeUp)
+        );
+    }
+
+    #[test]
+    fn limit_is_only_accepted_by_limited_modes() {
+        assert_eq!(
+            GameMode::from_name("survival", Some(30)),
+            Ok(GameMode::Survival { ticks: 30 })
+        );
+        assert!(GameMode::from_name("time-attack", Some(30)).is_ok());
+        assert!(GameMode::from_name("endless", Some(30)).is_err());
+        assert!(GameMode::from_name("daily", Some(30)).is_err());
+        assert_eq!(GameMode::from_name("endless", None), Ok(GameMode::Endless));
+    }
+}
@@ -0,0 +1,152 @@
+/*
+在这个文件里面定义渲染后端
+Canvas 只负责把游戏状态组装成一帧文本，真正输出到哪里由 Renderer 决定：
+CrosstermRenderer：通过 crossterm 输出到终端（默认）
+AnsiRenderer：只使用最基础的 ANSI 控制序列，适合不支持 crossterm 特性的简陋终端
+MemoryRenderer：把帧保存在内存中，方便测试对整帧做快照比较，无界面运行时也用它吞掉画面
+*/
+use crate::game::game_error::GameError;
+use crossterm::{
+    cursor::{Hide, MoveTo},
+    execute,
+    terminal::{Clear, ClearType},
+};
+use std::io::{self, Write, stdout};
+
+/// 渲染后端特征定义
+pub trait Renderer {
+    /// 从屏幕左上角开始输出一整帧
+    fn draw_frame(&mut self, frame: &str) -> Result<(), GameError>;
+    /// 清除整个屏幕（重新开始游戏时调用）
+    fn clear_screen(&mut self) -> Result<(), GameError>;
+}
+
+/// crossterm 终端后端，需要调用方自行进入交替屏幕等终端模式
+pub struct CrosstermRenderer;
+
+impl Renderer for CrosstermRenderer {
+    fn draw_frame(&mut self, frame: &str) -> Result<(), GameError> {
+        let mut stdout = stdout();
+        // 将光标复位到 (0,0) 并隐藏
+        execute!(stdout, MoveTo(0, 0), Hide)?;
+        // 一次性写出，极致顺滑；管道关闭时返回错误而不是 panic
+        stdout.write_all(frame.as_bytes())?;
+        stdout.flush()?;
+        Ok(())
+    }
+
+    fn clear_screen(&mut self) -> Result<(), GameError> {
+        execute!(
+            stdout(),
+            Clear(ClearType::All), // 清除所有字符
+            MoveTo(0, 0)           // 光标归位
+        )?;
+        Ok(())
+    }
+}
+
+/// 纯 ANSI 后端：只使用光标归位与清屏两个控制序列，可以写到任意输出流
+pub struct AnsiRenderer<W: Write> {
+    out: W,
+}
+
+impl AnsiRenderer<io::Stdout> {
+    /// 输出到标准输出
+    pub fn stdout() -> Self {
+        Self::new(stdout())
+    }
+}
+
+impl<W: Write> AnsiRenderer<W> {
+    // 光标归位
+    const CURSOR_HOME: &'static str = "\x1b[H";
+    // 清屏
+    const CLEAR_SCREEN: &'static str = "\x1b[2J";
+
+    /// 输出到任意实现了 Write 的目标
+    pub fn new(out: W) -> Self {
+        AnsiRenderer { out }
+    }
+}
+
+impl<W: Write> Renderer for AnsiRenderer<W> {
+    fn draw_frame(&mut self, frame: &str) -> Result<(), GameError> {
+        self.out.write_all(Self::CURSOR_HOME.as_bytes())?;
+        // 简陋终端在原始模式下不会把 \n 当作回车换行，这里显式补上 \r
+        self.out.write_all(frame.replace('\n', "\r\n").as_bytes())?;
+        self.out.flush()?;
+        Ok(())
+    }
+
+    fn clear_screen(&mut self) -> Result<(), GameError> {
+        self.out.write_all(Self::CLEAR_SCREEN.as_bytes())?;
+        self.out.write_all(Self::CURSOR_HOME.as_bytes())?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// 内存后端：只保存最近一帧，供测试做快照
+#[derive(Default)]
+pub struct MemoryRenderer {
+    last_frame: String,
+    frame_count: usize,
+}
+
+impl MemoryRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 最近一帧的文本快照
+    pub fn snapshot(&self) -> &str {
+        &self.last_frame
+    }
+
+    /// 总共绘制过多少帧
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+}
+
+impl Renderer for MemoryRenderer {
+    fn draw_frame(&mut self, frame: &str) -> Result<(), GameError> {
+        self.last_frame.clear();
+        self.last_frame.push_str(frame);
+        self.frame_count += 1;
+        Ok(())
+    }
+
+    fn clear_screen(&mut self) -> Result<(), GameError> {
+        self.last_frame.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_renderer_keeps_last_frame() {
+        let mut renderer = MemoryRenderer::new();
+        renderer.draw_frame("第一帧\n").unwrap();
+        renderer.draw_frame("第二帧\n").unwrap();
+        assert_eq!(renderer.snapshot(), "第二帧\n");
+        assert_eq!(renderer.frame_count(), 2);
+
+        renderer.clear_screen().unwrap();
+        assert_eq!(renderer.snapshot(), "");
+    }
+
+    #[test]
+    fn ansi_renderer_writes_plain_escape_sequences() {
+        let mut renderer = AnsiRenderer::new(Vec::new());
+        renderer.draw_frame("ab\ncd\n").unwrap();
+        renderer.clear_screen().unwrap();
+        assert_eq!(
+            String::from_utf8(renderer.out).unwrap(),
+            "\x1b[Hab\r\ncd\r\n\x1b[2J\x1b[H"
+        );
+    }
+}
@@ -1,22 +1,50 @@
+pub mod achievement;
+pub mod daily;
 pub mod game_direction;
 pub mod game_display;
+pub mod game_error;
+pub mod game_event;
+pub mod game_mode;
+pub mod game_renderer;
+pub mod replay;
 pub mod snake;
+pub mod telemetry;
 
+use game_direction::Direction;
+use game_display::Canvas;
+use game_error::GameError;
+use game_event::GameEvent;
+use game_mode::{GameMode, GameOutcome};
+use snake::Snake;
 use std::fs;
+use std::io::{self, Write};
 
+/// 每一帧（tick）的时长，单位毫秒
+pub const TICK_MILLIS: u64 = 250;
+/// 每得多少分升一级
+pub const SCORE_PER_LEVEL: usize = 5;
+
+/// 一局游戏的状态：运行/挂起标记、分数、模式、tick 计数与结局
 pub struct GameState {
     game_is_runing: bool,
     game_is_suspend: bool,
     game_score: usize,
     game_historic_score: usize,
+    game_mode: GameMode,
+    game_tick: u64,
+    game_outcome: Option<GameOutcome>,
+    //本 tick 内产生、尚未分发的事件
+    game_events: Vec<GameEvent>,
+    //本局是否已经宣布过破纪录，避免每吃一个食物都宣布一次
+    new_high_score_announced: bool,
 }
 
 impl GameState {
     const SCORE_FILE: &'static str = "game_data.txt";
-    const KEY_NAME: &'static str = "historic_score";
 
-    /// 1. 初始化：在创建 GameState 时自动从文件加载历史最高分
-    pub fn new() -> Self {
+    /// 1. 初始化：在创建 GameState 时自动从文件加载当前模式的历史最高分
+    pub fn new(game_mode: GameMode) -> Self {
+        let key_name = game_mode.score_key();
         let mut historic_score = 0;
 
         // 尝试读取文件
@@ -24,7 +52,7 @@ impl GameState {
             // 逐行解析，寻找 historic_score:XX
             for line in content.lines() {
                 if let Some((key, value)) = line.split_once(':')
-                    && key.trim() == Self::KEY_NAME
+                    && key.trim() == key_name
                 {
                     historic_score = value.trim().parse::<usize>().unwrap_or(0);
                     break;
@@ -32,22 +60,37 @@ impl GameState {
             }
         }
 
+        Self::with_historic_score(game_mode, historic_score)
+    }
+
+    /// 使用给定的历史最高分创建 GameState，不读取存档文件
+    pub fn with_historic_score(game_mode: GameMode, historic_score: usize) -> Self {
         GameState {
             game_is_runing: true,
             game_is_suspend: false,
             game_score: 0,
             game_historic_score: historic_score,
+            game_mode,
+            game_tick: 0,
+            game_outcome: None,
+            game_events: Vec::new(),
+            new_high_score_announced: false,
         }
     }
 
-    /// 2. 修改与保存：只修改对应行，保留其他行内容
-    pub fn save_historic_score(&mut self) {
+    /// 2. 修改与保存：只修改对应行，保留其他行内容；当前分数没有突破历史最高分时不会写文件
+    pub fn save_historic_score(&mut self) -> Result<(), GameError> {
         // 逻辑：只有当前分数突破记录才触发写入，减少磁盘 IO
         if self.game_score <= self.game_historic_score {
-            println!("很遗憾！您没能突破历史最高分！！！",);
-            return;
+            writeln!(io::stdout().lock(), "很遗憾！您没能突破历史最高分！！！")
+                .map_err(GameError::Terminal)?;
+            return Ok(());
         }
-        println!("哇塞！您突破了历史最高分，正在为您保存游戏得分！",);
+        writeln!(
+            io::stdout().lock(),
+            "哇塞！您突破了历史最高分，正在为您保存游戏得分！"
+        )
+        .map_err(GameError::Terminal)?;
         self.game_historic_score = self.game_score;
 
         // 读取现有内容，如果文件不存在则默认为空字符串
@@ -56,13 +99,14 @@ impl GameState {
         // 将文件内容转为行列表，同时过滤掉可能因编辑器产生的空白行
         let mut lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
 
+        let key_name = self.game_mode.score_key();
         let mut found = false;
-        let new_line = format!("{}: {}", Self::KEY_NAME, self.game_historic_score);
+        let new_line = format!("{}: {}", key_name, self.game_historic_score);
 
         // 遍历寻找并替换
         for line in lines.iter_mut() {
             if let Some((key, _)) = line.split_once(':')
-                && key.trim() == Self::KEY_NAME
+                && key.trim() == key_name
             {
                 *line = new_line.clone();
                 found = true;
@@ -80,9 +124,7 @@ impl GameState {
         final_content.push('\n');
 
         // 覆写回磁盘
-        if let Err(e) = fs::write(Self::SCORE_FILE, final_content) {
-            eprintln!("保存游戏数据失败: {}", e);
-        }
+        fs::write(Self::SCORE_FILE, final_content).map_err(GameError::SaveData)
     }
     pub fn get_game_is_runing(&self) -> bool {
         self.game_is_runing
@@ -105,4 +147,99 @@ impl GameState {
     pub fn get_game_historic_score(&self) -> usize {
         self.game_historic_score
     }
+    pub fn get_game_mode(&self) -> GameMode {
+        self.game_mode
+    }
+    pub fn get_game_tick(&self) -> u64 {
+        self.game_tick
+    }
+    pub fn get_game_outcome(&self) -> Option<GameOutcome> {
+        self.game_outcome
+    }
+    /// 当前等级，从 1 开始，每 SCORE_PER_LEVEL 分升一级
+    pub fn get_game_level(&self) -> usize {
+        self.game_score / SCORE_PER_LEVEL + 1
+    }
+    /// 记录游戏结局，同时把游戏挂起等待玩家重新开始或退出
+    pub fn set_game_outcome(&mut self, outcome: GameOutcome) {
+        self.game_outcome = Some(outcome);
+        self.set_game_is_suspend(true);
+        self.emit(if outcome.is_win() {
+            GameEvent::Won { outcome }
+        } else {
+            GameEvent::Died { outcome }
+        });
+    }
+    /// 记录一个事件，等待本 tick 结束后统一分发
+    pub fn emit(&mut self, event: GameEvent) {
+        self.game_events.push(event);
+    }
+    /// 取出所有尚未分发的事件
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.game_events)
+    }
+}
+
+/// 按画布大小和模式创建一条新蛇：指定了种子时食物位置可以复现，每日挑战还会放上当天的障碍物
+pub fn spawn_snake(canvas: &Canvas, mode: GameMode, seed: Option<u64>) -> Snake {
+    let (width, height) = (canvas.get_canvas_width(), canvas.get_canvas_height());
+    // 画布只有 40x15 和 80x30 两种尺寸，都放得下初始的蛇和食物
+    let mut snake = Snake::new(width, height).expect("画布放不下初始的蛇和食物");
+    if let Some(seed) = seed {
+        snake.set_food_seed(seed);
+    }
+    if let Some(seed) = mode.daily_seed() {
+        let body: Vec<_> = snake.get_snake_body().iter().copied().collect();
+        let direction = snake.get_head_direction().to_vec();
+        snake.place_obstacles(daily::obstacle_layout(
+            seed, width, height, &body, direction,
+        ));
+    }
+    snake
+}
+
+/// 按输入指令给蛇转向：掉头、重复方向以及非方向指令会被忽略，成功转向时记录 Turned 事件
+pub fn steer(snake: &mut Snake, game_state: &mut GameState, input: Direction) -> bool {
+    let from = snake.get_head_direction();
+    match from.apply(input) {
+        Some(to) => {
+            snake.set_head_direction(to);
+            game_state.emit(GameEvent::Turned { from, to });
+            true
+        }
+        None => false,
+    }
+}
+
+/// 推进一帧：移动蛇、累计 tick，并按当前模式检查胜负与自动增长
+pub fn step(snake: &mut Snake, game_state: &mut GameState) {
+    let level_before = game_state.get_game_level();
+    snake.snake_move(game_state);
+
+    // 分数变化带来的升级与破纪录事件
+    let level = game_state.get_game_level();
+    if level > level_before {
+        game_state.emit(GameEvent::LevelUp { level });
+    }
+    let score = game_state.game_score;
+    if score > game_state.game_historic_score && !game_state.new_high_score_announced {
+        game_state.new_high_score_announced = true;
+        game_state.emit(GameEvent::NewHighScore {
+            score,
+            previous: game_state.game_historic_score,
+        });
+    }
+
+    if game_state.get_game_is_suspend() {
+        return;
+    }
+
+    game_state.game_tick += 1;
+    let mode = game_state.game_mode;
+    if mode.should_auto_grow(game_state.game_tick) {
+        snake.grow();
+    }
+    if let Some(outcome) = mode.check_finished(game_state.game_tick) {
+        game_state.set_game_outcome(outcome);
+    }
 }
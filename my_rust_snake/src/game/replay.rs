@@ -0,0 +1,395 @@
+/*
+在这个文件里面实现对局录像与回放导出
+录像 = 对局参数（模式、种子、画布、历史最高分）+ 每个 tick 的输入，
+录像文件本身就是 ScriptInput 格式的脚本，对局参数写在开头的注释里；
+导出时按同样的种子和输入重新模拟一遍，再把每一帧输出成 asciinema v2 (.cast) 或 GIF 动画
+*/
+use crate::game::daily::ChallengeDate;
+use crate::game::game_direction::{Direction, InputDevice, ScriptInput};
+use crate::game::game_display::Canvas;
+use crate::game::game_error::GameError;
+use crate::game::game_mode::GameMode;
+use crate::game::{GameState, TICK_MILLIS, spawn_snake, steer, step};
+use std::io::{self, Write};
+
+/// 录像文件的第一行
+const RECORDING_TITLE: &str = "# my_rust_snake 录像";
+/// 录像中每行最多写多少个指令
+const TOKENS_PER_LINE: usize = 16;
+/// GIF 中每个格子的边长（像素）
+const GIF_CELL_PIXELS: usize = 8;
+/// GIF 最后一帧停留的时间（百分之一秒），让结局画面能看清
+const GIF_LAST_FRAME_DELAY: u16 = 200;
+/// GIF 调色板：背景、边框、蛇头、蛇身、食物、障碍物
+const GIF_PALETTE: [u8; 18] = [
+    24, 24, 24, //
+    96, 96, 96, //
+    96, 232, 96, //
+    32, 160, 32, //
+    232, 56, 56, //
+    200, 200, 200,
+];
+
+/// 一局游戏的录像
+#[derive(Clone, PartialEq, Debug)]
+pub struct Recording {
+    mode: GameMode,
+    seed: u64,
+    half_block: bool,
+    historic_score: usize,
+    // 每个 tick 的输入，None 表示这个 tick 没有操作
+    inputs: Vec<Option<Direction>>,
+}
+
+impl Recording {
+    pub fn new(mode: GameMode, seed: u64, half_block: bool, historic_score: usize) -> Self {
+        Recording {
+            mode,
+            seed,
+            half_block,
+            historic_score,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// 记录一个 tick 的输入，游戏进行中的重新开始指令不起作用，按没有操作记录
+    pub fn push(&mut self, input: Option<Direction>) {
+        self.inputs.push(input.filter(|d| *d != Direction::Restart));
+    }
+
+    /// 转换成录像文件：对局参数写在注释里，输入按 ScriptInput 格式合并连续相同的指令
+    pub fn to_script(&self) -> String {
+        let mut script = format!("{}\n# mode: {}\n", RECORDING_TITLE, self.mode.name());
+        if let Some(limit) = self.mode.limit() {
+            script.push_str(&format!("# limit: {}\n", limit));
+        }
+        if let GameMode::Daily { date } = self.mode {
+            script.push_str(&format!("# date: {}\n", date));
+        }
+        script.push_str(&format!(
+            "# seed: {}\n# half-block: {}\n# historic: {}\n",
+            self.seed, self.half_block, self.historic_score
+        ));
+
+        let mut tokens = Vec::new();
+        for input in &self.inputs {
+            match tokens.last_mut() {
+                Some((last, count)) if last == input => *count += 1,
+                _ => tokens.push((*input, 1)),
+            }
+        }
+        let tokens: Vec<String> = tokens
+            .into_iter()
+            .map(|(input, count)| {
+                let command = match input {
+                    Some(Direction::Up) => "U",
+                    Some(Direction::Down) => "D",
+                    Some(Direction::Left) => "L",
+                    Some(Direction::Right) => "R",
+                    Some(Direction::Quit) => "Q",
+                    Some(Direction::Restart) | None => ".",
+                };
+                if count == 1 {
+                    command.to_string()
+                } else {
+                    format!("{}{}", command, count)
+                }
+            })
+            .collect();
+        for line in tokens.chunks(TOKENS_PER_LINE) {
+            script.push_str(&line.join(" "));
+            script.push('\n');
+        }
+        script
+    }
+
+    /// 解析 to_script 生成的录像文件
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut fields = Vec::new();
+        for line in text.lines() {
+            if let Some(comment) = line.trim().strip_prefix('#')
+                && let Some((key, value)) = comment.split_once(':')
+            {
+                fields.push((key.trim(), value.trim()));
+            }
+        }
+        let field = |name: &str| fields.iter().find(|(key, _)| *key == name).map(|f| f.1);
+        let number = |name: &str| -> Result<Option<u64>, String> {
+            field(name)
+                .map(|v| {
+                    v.parse::<u64>()
+                        .map_err(|_| format!("录像中的 {} 无效：{}", name, v))
+                })
+                .transpose()
+        };
+
+        let mut mode =
+            GameMode::from_name(field("mode").ok_or("录像缺少 mode")?, number("limit")?)?;
+        if let GameMode::Daily { .. } = mode {
+            let date = field("date").ok_or("每日挑战的录像缺少 date")?;
+            mode = GameMode::Daily {
+                date: ChallengeDate::parse(date)?,
+            };
+        }
+        let seed = number("seed")?.ok_or("录像缺少 seed")?;
+        let half_block = field("half-block") == Some("true");
+        let historic_score = number("historic")?.unwrap_or(0) as usize;
+
+        // 指令部分直接交给 ScriptInput 解析，读到退出指令（或脚本结束）为止
+        let mut script = ScriptInput::parse(text)?;
+        let mut recording = Recording::new(mode, seed, half_block, historic_score);
+        loop {
+            match script.direction_check() {
+                Some(Direction::Quit) => break,
+                input => recording.inputs.push(input),
+            }
+        }
+        Ok(recording)
+    }
+
+    /// 按录像重新模拟整局游戏，返回开局画面以及之后每个 tick 的画面
+    pub fn replay(&self) -> Replay {
+        let mut canvas = if self.half_block {
+            Canvas::with_half_block()
+        } else {
+            Canvas::new()
+        };
+        let mut snake = spawn_snake(&canvas, self.mode, Some(self.seed));
+        let mut state = GameState::with_historic_score(self.mode, self.historic_score);
+        let mut frames = Vec::new();
+        let mut capture = |canvas: &mut Canvas, snake: &_, state: &GameState| {
+            frames.push(ReplayFrame {
+                text: canvas.compose_frame(snake, state),
+                cells: canvas.compose_cells(snake).to_vec(),
+            });
+        };
+
+        capture(&mut canvas, &snake, &state);
+        for input in &self.inputs {
+            if let Some(direction) = *input {
+                steer(&mut snake, &mut state, direction);
+            }
+            step(&mut snake, &mut state);
+            state.drain_events();
+            capture(&mut canvas, &snake, &state);
+            if state.get_game_is_suspend() {
+                break;
+            }
+        }
+
+        let layout = canvas.layout();
+        Replay {
+            width: canvas.get_canvas_width(),
+            height: canvas.get_canvas_height(),
+            columns: layout.map_columns() + 2,
+            frames,
+        }
+    }
+}
+
+/// 回放中的一帧：终端画面文本以及棋盘格子
+pub struct ReplayFrame {
+    pub text: String,
+    pub cells: Vec<char>,
+}
+
+/// 重新模拟得到的整局画面，每帧间隔一个 tick
+pub struct Replay {
+    width: usize,
+    height: usize,
+    columns: usize,
+    frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    pub fn frames(&self) -> &[ReplayFrame] {
+        &self.frames
+    }
+
+    /// 导出成 asciinema v2 格式：第一行是头部，之后每帧一行 [时间, "o", 输出]
+    pub fn to_asciicast(&self) -> String {
+        let rows = self
+            .frames
+            .iter()
+            .map(|f| f.text.lines().count())
+            .max()
+            .unwrap_or(0);
+        let mut cast = format!(
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"title\": \"my_rust_snake\"}}\n",
+            self.columns, rows
+        );
+        for (i, frame) in self.frames.iter().enumerate() {
+            // 第一帧先清屏并隐藏光标，之后每帧回到左上角覆盖上一帧
+            let prefix = if i == 0 {
+                "\x1b[?25l\x1b[2J\x1b[H"
+            } else {
+                "\x1b[H"
+            };
+            let output = format!("{}{}", prefix, frame.text.replace('\n', "\r\n"));
+            cast.push_str(&format!(
+                "[{:.3}, \"o\", {}]\n",
+                (i as u64 * TICK_MILLIS) as f64 / 1000.0,
+                json_string(&output)
+            ));
+        }
+        cast
+    }
+
+    /// 导出成 GIF 动画：每个格子画成一个色块，外面加一圈边框
+    pub fn write_gif<W: Write>(&self, out: W) -> Result<(), GameError> {
+        let (cells_wide, cells_high) = (self.width + 2, self.height + 2);
+        let pixels_wide = cells_wide * GIF_CELL_PIXELS;
+        let pixels_high = cells_high * GIF_CELL_PIXELS;
+        let too_large = || {
+            GameError::Export(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "棋盘太大，无法导出 GIF",
+            ))
+        };
+        let width = u16::try_from(pixels_wide).map_err(|_| too_large())?;
+        let height = u16::try_from(pixels_high).map_err(|_| too_large())?;
+
+        let mut encoder = gif::Encoder::new(out, width, height, &GIF_PALETTE).map_err(gif_error)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(gif_error)?;
+        for (i, frame) in self.frames.iter().enumerate() {
+            let mut pixels = vec![0u8; pixels_wide * pixels_high];
+            for cell_y in 0..cells_high {
+                for cell_x in 0..cells_wide {
+                    let border = cell_x == 0
+                        || cell_y == 0
+                        || cell_x == cells_wide - 1
+                        || cell_y == cells_high - 1;
+                    let color = if border {
+                        1
+                    } else {
+                        palette_index(frame.cells[(cell_y - 1) * self.width + cell_x - 1])
+                    };
+                    for y in 0..GIF_CELL_PIXELS {
+                        let start =
+                            (cell_y * GIF_CELL_PIXELS + y) * pixels_wide + cell_x * GIF_CELL_PIXELS;
+                        pixels[start..start + GIF_CELL_PIXELS].fill(color);
+                    }
+                }
+            }
+            let mut gif_frame = gif::Frame::from_indexed_pixels(width, height, pixels, None);
+            gif_frame.delay = if i + 1 == self.frames.len() {
+                GIF_LAST_FRAME_DELAY
+            } else {
+                (TICK_MILLIS / 10) as u16
+            };
+            encoder.write_frame(&gif_frame).map_err(gif_error)?;
+        }
+        // 写入文件尾并刷新，缓冲区里的错误也要报告出来
+        let mut out = encoder.into_inner().map_err(GameError::Export)?;
+        out.flush().map_err(GameError::Export)
+    }
+}
+
+/// 格子字符对应的调色板下标
+fn palette_index(symbol: char) -> u8 {
+    match symbol {
+        'O' => 2,
+        '■' => 3,
+        '$' => 4,
+        '#' => 5,
+        _ => 0,
+    }
+}
+
+fn gif_error(e: gif::EncodingError) -> GameError {
+    match e {
+        gif::EncodingError::Io(e) => GameError::Export(e),
+        other => GameError::Export(io::Error::other(other)),
+    }
+}
+
+/// 把文本转换成 JSON 字符串字面量，控制字符（包括 ESC）用 \u 转义
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording(inputs: &str) -> Recording {
+        let mut recording = Recording::new(GameMode::TimeAttack { seconds: 30 }, 42, false, 7);
+        let mut script = ScriptInput::parse(inputs).unwrap();
+        loop {
+            match script.direction_check() {
+                Some(Direction::Quit) => break,
+                input => recording.push(input),
+            }
+        }
+        recording
+    }
+
+    #[test]
+    fn recording_round_trips_through_script() {
+        let recording = recording("R R . . . D L2 U");
+        let script = recording.to_script();
+        assert_eq!(
+            script,
+            "# my_rust_snake 录像\n# mode: time-attack\n# limit: 30\n# seed: 42\n\
+             # half-block: false\n# historic: 7\nR2 .3 D L2 U\n"
+        );
+        assert_eq!(Recording::parse(&script).unwrap(), recording);
+
+        let date = ChallengeDate::parse("2026-10-18").unwrap();
+        let daily = Recording::new(GameMode::Daily { date }, date.seed(), true, 0);
+        assert_eq!(Recording::parse(&daily.to_script()).unwrap(), daily);
+        assert!(Recording::parse("R R").is_err());
+    }
+
+    #[test]
+    fn replay_is_deterministic() {
+        // 初始蛇头 (5,5) 向右，下移三格吃到 (5,8) 的食物
+        let replay = recording("D3 R2").replay();
+        assert_eq!(replay.frames().len(), 6);
+        assert!(replay.frames()[0].text.contains("当前分数：0"));
+        assert!(replay.frames()[3].text.contains("当前分数：1"));
+        let again = recording("D3 R2").replay();
+        for (a, b) in replay.frames().iter().zip(again.frames()) {
+            assert_eq!(a.text, b.text);
+        }
+
+        // 撞墙后回放停止，不再模拟剩下的输入
+        let replay = recording("U10").replay();
+        assert_eq!(replay.frames().len(), 7);
+        assert!(replay.frames()[6].text.contains("游戏结束"));
+    }
+
+    #[test]
+    fn exports_asciicast_and_gif() {
+        let replay = recording("D2").replay();
+        let cast = replay.to_asciicast();
+        let lines: Vec<&str> = cast.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("{\"version\": 2, \"width\": 82, \"height\": 23"));
+        assert!(lines[1].starts_with("[0.000, \"o\", \"\\u001b[?25l\\u001b[2J\\u001b[H◆"));
+        assert!(lines[3].starts_with("[0.500, \"o\", \"\\u001b[H◆"));
+        assert!(lines[2].contains("█\\r\\n█"));
+
+        let mut gif = Vec::new();
+        replay.write_gif(&mut gif).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+        // 逻辑屏幕大小：(40+2)*8 x (15+2)*8
+        assert_eq!(&gif[6..10], &[80, 1, 136, 0]);
+    }
+}
@@ -1,17 +1,89 @@
-use crate::game::{GameState, game_direction::Direction};
-use rand::Rng;
+use crate::game::{
+    GameState, game_direction::Direction, game_event::GameEvent, game_mode::GameOutcome,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::VecDeque;
-use std::{thread, time::Duration};
 //第一，定义蛇的数据结构，以及初始化蛇
+/// 棋盘坐标：x 为列号，y 为行号，左上角为 (0, 0)
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub struct Position {
     //坐标结构体
     pub x: i32,
     pub y: i32,
 }
+
+/// 棋盘占用表：与 body 同步维护，碰撞检测只需 O(1) 查表
+struct OccupancyGrid {
+    width: usize,
+    height: usize,
+    cells: Vec<bool>,
+    occupied: usize,
+}
+
+impl OccupancyGrid {
+    fn new(width: usize, height: usize) -> Self {
+        OccupancyGrid {
+            width,
+            height,
+            cells: vec![false; width * height],
+            occupied: 0,
+        }
+    }
+    //坐标在棋盘内时返回一维索引 (y * W) + x
+    fn index(&self, position: &Position) -> Option<usize> {
+        let in_bounds = position.x >= 0
+            && (position.x as usize) < self.width
+            && position.y >= 0
+            && (position.y as usize) < self.height;
+        in_bounds.then(|| position.y as usize * self.width + position.x as usize)
+    }
+    fn is_occupied(&self, position: &Position) -> bool {
+        self.index(position).is_some_and(|i| self.cells[i])
+    }
+    //只用于已经确认在棋盘内的坐标：构造时校验过蛇身，移动前检查过是否撞墙
+    fn set(&mut self, position: &Position, occupied: bool) {
+        let index = self.index(position).expect("蛇身超出了棋盘范围");
+        if self.cells[index] != occupied {
+            self.cells[index] = occupied;
+            if occupied {
+                self.occupied += 1;
+            } else {
+                self.occupied -= 1;
+            }
+        }
+    }
+    fn free_count(&self) -> usize {
+        self.cells.len() - self.occupied
+    }
+    //第 n 个空闲格子的坐标（按行优先顺序）
+    fn nth_free(&self, n: usize) -> Option<Position> {
+        self.positions(false).nth(n)
+    }
+    //所有空闲或已占用格子的坐标（按行优先顺序）
+    fn positions(&self, occupied: bool) -> impl Iterator<Item = Position> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(move |(_, cell)| **cell == occupied)
+            .map(|(i, _)| Position {
+                x: (i % self.width) as i32,
+                y: (i / self.width) as i32,
+            })
+    }
+}
+
+/// 蛇：维护蛇身、方向、食物以及棋盘占用情况
+///
+/// 每调用一次 [`Snake::snake_move`]（或 [`crate::game::step`]）蛇前进一格，
+/// 撞墙、撞到自己或吃满棋盘时把结局记录到 [`GameState`]
 pub struct Snake {
     // 蛇的身体，front() = 头，back() = 尾
     body: VecDeque<Position>,
+    // 蛇身和障碍物占用的格子，与 body 保持同步
+    grid: OccupancyGrid,
+    // 障碍物占用的格子，障碍物放下之后不再变化
+    obstacles: OccupancyGrid,
     // 蛇头位置
     head_position: Position,
     // 蛇头方向
@@ -20,30 +92,73 @@ pub struct Snake {
     snake_body_length: usize,
     //食物坐标
     snak_food_position: Position,
+    //等待增长的节数，移动时尾部暂不收缩
+    pending_growth: usize,
+    //食物生成使用的随机数生成器，固定种子即可复现整局游戏
+    rng: StdRng,
 }
 impl Snake {
-    //初始化蛇
-    pub fn new() -> Self {
+    /// 初始化蛇，棋盘大小与画布一致；棋盘放不下初始的蛇和食物时返回错误
+    pub fn new(canvas_width: usize, canvas_height: usize) -> Result<Self, String> {
         let head_position = Position { x: 5, y: 5 };
-        let snake_body_length = 3;
-        let mut snake = Snake {
-            body: VecDeque::new(),
-            head_direction: Direction::Right,
-            head_position,
-            snake_body_length,
-            snak_food_position: Position { x: 5, y: 8 },
-        };
+        let snake_body_length: i32 = 3;
         //初始化蛇身每一个部分的位置
-        for i in 0..(snake_body_length as i32) {
-            let pos = Position {
-                x: head_position.x - i,
-                y: head_position.y,
-            };
-            snake.body.push_back(pos);
+        let body = (0..snake_body_length).map(|i| Position {
+            x: head_position.x - i,
+            y: head_position.y,
+        });
+        Self::from_body(
+            body,
+            Direction::Right,
+            Position { x: 5, y: 8 },
+            canvas_width,
+            canvas_height,
+        )
+    }
+    /// 用指定的蛇身（第一个元素为蛇头）、方向和食物位置构造蛇；
+    /// 蛇身为空、超出棋盘或者有重叠的格子，以及食物不在空闲格子上时返回错误
+    pub fn from_body(
+        body: impl IntoIterator<Item = Position>,
+        head_direction: Direction,
+        snak_food_position: Position,
+        canvas_width: usize,
+        canvas_height: usize,
+    ) -> Result<Self, String> {
+        let body: VecDeque<Position> = body.into_iter().collect();
+        let head_position = *body.front().ok_or("蛇身至少需要一节")?;
+        let mut grid = OccupancyGrid::new(canvas_width, canvas_height);
+        for pos in &body {
+            if grid.index(pos).is_none() {
+                return Err(format!(
+                    "蛇身 ({}, {}) 超出了 {}x{} 的棋盘",
+                    pos.x, pos.y, canvas_width, canvas_height
+                ));
+            }
+            if grid.is_occupied(pos) {
+                return Err(format!("蛇身在 ({}, {}) 重叠", pos.x, pos.y));
+            }
+            grid.set(pos, true);
         }
-        snake
+        //食物必须放在棋盘内的空闲格子上，否则蛇永远吃不到
+        if grid.index(&snak_food_position).is_none() || grid.is_occupied(&snak_food_position) {
+            return Err(format!(
+                "食物 ({}, {}) 不在 {}x{} 棋盘的空闲格子上",
+                snak_food_position.x, snak_food_position.y, canvas_width, canvas_height
+            ));
+        }
+        Ok(Snake {
+            snake_body_length: body.len(),
+            body,
+            grid,
+            obstacles: OccupancyGrid::new(canvas_width, canvas_height),
+            head_direction,
+            head_position,
+            snak_food_position,
+            pending_growth: 0,
+            rng: StdRng::from_entropy(),
+        })
     }
-    //预判下一步蛇头位置
+    /// 预判下一步蛇头位置
     pub fn predicted_position(
         &self,
         snake_head_current_position: Position,
@@ -56,78 +171,102 @@ impl Snake {
         }
     }
     //定义蛇的碰撞检测逻辑
-    pub fn is_out_of_bounds(
-        &self,
-        snake_head_next_position: &Position,
-        canvas_width: usize,
-        canvas_height: usize,
-    ) -> bool {
-        snake_head_next_position.x < 0
-            || snake_head_next_position.x >= canvas_width as i32
-            || snake_head_next_position.y < 0
-            || snake_head_next_position.y >= canvas_height as i32
+    /// 坐标是否超出棋盘
+    pub fn is_out_of_bounds(&self, snake_head_next_position: &Position) -> bool {
+        self.grid.index(snake_head_next_position).is_none()
     }
-    pub fn is_touch_self_body(
-        &self,
-        snake_head_next_position: &Position,
-        body: &VecDeque<Position>,
-    ) -> bool {
-        // contains 会逐个比较 body 中的元素是否等于 snake_head_current_position
-        // 前提是这个元素得实现可比较的特性
-        body.contains(snake_head_next_position)
-    }
-    //定义移动一次蛇的方发
-    pub fn snake_move(
-        &mut self,
-        game_state: &mut GameState,
-        canvas_width: usize,
-        canvas_height: usize,
-    ) {
+    /// 坐标是否与蛇身重叠
+    pub fn is_touch_self_body(&self, snake_head_next_position: &Position) -> bool {
+        // 查占用表即可，不再逐个比较 body 中的元素
+        self.grid.is_occupied(snake_head_next_position)
+            && !self.is_obstacle(snake_head_next_position)
+    }
+    /// 坐标上是否有障碍物
+    pub fn is_obstacle(&self, position: &Position) -> bool {
+        self.obstacles.is_occupied(position)
+    }
+    /// 定义移动一次蛇的方发
+    pub fn snake_move(&mut self, game_state: &mut GameState) {
         let next_position = self.predicted_position(self.head_position, self.head_direction);
         //判断是否符合移动条件
-        if self.is_out_of_bounds(&next_position, canvas_width, canvas_height) {
-            game_state.set_game_is_suspend(true);
-            println!("您操控的蛇出界！如果重新开始游戏请按下：'r'");
-            thread::sleep(Duration::from_millis(3000));
-        } else if self.is_touch_self_body(&next_position, &self.body) {
-            game_state.set_game_is_suspend(true);
-            println!("您操控的蛇与自身碰撞！如果重新开始游戏请按下：'r'");
-            thread::sleep(Duration::from_millis(3000));
+        if self.is_out_of_bounds(&next_position) {
+            game_state.set_game_outcome(GameOutcome::HitWall);
+        } else if self.is_obstacle(&next_position) {
+            game_state.set_game_outcome(GameOutcome::HitObstacle);
+        } else if self.is_touch_self_body(&next_position) {
+            game_state.set_game_outcome(GameOutcome::HitSelf);
         } else {
             self.body.push_front(next_position);
+            self.grid.set(&next_position, true);
             if next_position == self.snak_food_position {
                 self.snake_body_length += 1;
-                self.generate_food(canvas_width, canvas_height);
-            } else {
-                self.body.pop_back();
+                let score = game_state.get_game_score() + 1;
+                game_state.set_game_score(score);
+                game_state.emit(GameEvent::FoodEaten {
+                    score,
+                    length: self.snake_body_length,
+                    position: next_position,
+                });
+                if !self.generate_food() {
+                    game_state.set_game_outcome(GameOutcome::BoardFull);
+                }
+            } else if self.pending_growth > 0 {
+                self.pending_growth -= 1;
+                self.snake_body_length += 1;
+                game_state.emit(GameEvent::Grew {
+                    length: self.snake_body_length,
+                });
+            } else if let Some(tail) = self.body.pop_back() {
+                self.grid.set(&tail, false);
             }
             //更新位置
             self.set_head_position(next_position);
         }
     }
 
-    //定义实物的生成逻辑
-    fn generate_food(&mut self, canvas_width: usize, canvas_height: usize) {
-        let mut rng = rand::thread_rng();
-        let mut snak_food_position = Position {
-            x: rng.gen_range(1..canvas_width) as i32,
-            y: rng.gen_range(1..canvas_height) as i32,
-        };
-        loop {
-            //食物保证不生成在蛇身体的坐标上
-            if self.body.contains(&snak_food_position) {
-                snak_food_position = Position {
-                    x: rng.gen_range(1..canvas_width) as i32,
-                    y: rng.gen_range(1..canvas_height) as i32,
-                };
-            } else {
-                self.snak_food_position = snak_food_position;
-                break;
+    /// 让蛇在接下来的移动中增长一节（生存挑战自动增长使用）
+    pub fn grow(&mut self) {
+        self.pending_growth += 1;
+    }
+
+    //定义实物的生成逻辑：在所有空闲格子中等概率选一个
+    //（旧实现用 gen_range(1..w) 反复抽样，既不会落在第 0 行/列，蛇填满棋盘时还会死循环）
+    //棋盘已被蛇填满、没有空位时返回 false
+    fn generate_food(&mut self) -> bool {
+        let free_count = self.grid.free_count();
+        if free_count == 0 {
+            return false;
+        }
+        let n = self.rng.gen_range(0..free_count);
+        match self.grid.nth_free(n) {
+            Some(position) => {
+                self.snak_food_position = position;
+                true
             }
+            None => false,
         }
     }
 
-    //获取蛇的数据结构队列
+    /// 在棋盘上放置障碍物：超出棋盘或与蛇身重叠的位置会被跳过，
+    /// 食物被障碍物盖住时重新生成
+    pub fn place_obstacles(&mut self, positions: impl IntoIterator<Item = Position>) {
+        for position in positions {
+            if self.is_out_of_bounds(&position) || self.grid.is_occupied(&position) {
+                continue;
+            }
+            self.grid.set(&position, true);
+            self.obstacles.set(&position, true);
+        }
+        if self.is_obstacle(&self.snak_food_position) {
+            self.generate_food();
+        }
+    }
+    /// 所有障碍物的坐标（按行优先顺序）
+    pub fn get_obstacles(&self) -> impl Iterator<Item = Position> + '_ {
+        self.obstacles.positions(true)
+    }
+
+    /// 获取蛇的数据结构队列
     pub fn get_snake_body(&self) -> &VecDeque<Position> {
         &self.body
     }
@@ -137,9 +276,9 @@ impl Snake {
     pub fn set_head_direction(&mut self, new_direction: Direction) {
         self.head_direction = new_direction;
     }
-    // pub fn get_head_position(&self) -> Position {
-    //     self.head_position
-    // }
+    pub fn get_head_position(&self) -> Position {
+        self.head_position
+    }
     pub fn set_head_position(&mut self, new_position: Position) {
         self.head_position = new_position;
     }
@@ -152,4 +291,264 @@ impl Snake {
     pub fn get_snak_food_position(&self) -> Position {
         self.snak_food_position
     }
+    /// 固定食物生成的随机种子
+    pub fn set_food_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game_mode::GameMode;
+    use crate::game::step;
+    use std::collections::HashSet;
+
+    const GAMES: u64 = 2000;
+    const MAX_TICKS: usize = 400;
+
+    /// 随机生成一局的输入：大部分 tick 没有输入，其余随机给出方向
+    fn random_input(rng: &mut StdRng) -> Option<Direction> {
+        match rng.gen_range(0..8) {
+            0 => Some(Direction::Up),
+            1 => Some(Direction::Down),
+            2 => Some(Direction::Left),
+            3 => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    fn random_mode(rng: &mut StdRng) -> GameMode {
+        match rng.gen_range(0..3) {
+            0 => GameMode::Endless,
+            1 => GameMode::TimeAttack { seconds: 30 },
+            _ => GameMode::Survival { ticks: 300 },
+        }
+    }
+
+    fn check_invariants(snake: &Snake, state: &GameState, seed: u64, tick: usize) {
+        let body = snake.get_snake_body();
+        assert_eq!(
+            body.len(),
+            snake.snake_body_length,
+            "种子 {} 第 {} tick：蛇身长度与 snake_body_length 不一致",
+            seed,
+            tick
+        );
+        let unique: HashSet<(i32, i32)> = body.iter().map(|p| (p.x, p.y)).collect();
+        assert_eq!(
+            unique.len(),
+            body.len(),
+            "种子 {} 第 {} tick：蛇身存在重复坐标 {:?}",
+            seed,
+            tick,
+            body
+        );
+        let occupied: HashSet<(i32, i32)> = snake
+            .grid
+            .cells
+            .iter()
+            .enumerate()
+            .filter(|(_, occupied)| **occupied)
+            .map(|(i, _)| ((i % snake.grid.width) as i32, (i / snake.grid.width) as i32))
+            .collect();
+        assert_eq!(
+            occupied, unique,
+            "种子 {} 第 {} tick：占用表与蛇身不同步",
+            seed, tick
+        );
+        // 棋盘被填满时已经没有地方放食物了
+        if state.get_game_outcome() == Some(GameOutcome::BoardFull) {
+            return;
+        }
+        assert!(
+            !snake.is_out_of_bounds(&snake.get_snak_food_position()),
+            "种子 {} 第 {} tick：食物 {:?} 超出了棋盘",
+            seed,
+            tick,
+            snake.get_snak_food_position()
+        );
+        assert!(
+            !body.contains(&snake.get_snak_food_position()),
+            "种子 {} 第 {} tick：食物 {:?} 生成在了蛇身上",
+            seed,
+            tick,
+            snake.get_snak_food_position()
+        );
+    }
+
+    /// 用随机棋盘、随机模式和随机输入序列跑完一局，每个 tick 检查不变量
+    fn simulate(seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        // 初始蛇头在 (5,5)，食物在 (5,8)，棋盘至少要容纳它们
+        let width = rng.gen_range(6..=40);
+        let height = rng.gen_range(9..=15);
+        let mut state = GameState::with_historic_score(random_mode(&mut rng), 0);
+        let mut snake = Snake::new(width, height).unwrap();
+        snake.set_food_seed(seed);
+
+        check_invariants(&snake, &state, seed, 0);
+        for tick in 1..=MAX_TICKS {
+            let old_direction = snake.get_head_direction();
+            if let Some(dir) = random_input(&mut rng)
+                && let Some(new_dir) = old_direction.apply(dir)
+            {
+                snake.set_head_direction(new_dir);
+            }
+            assert!(
+                !old_direction.is_opposite(snake.get_head_direction()),
+                "种子 {} 第 {} tick：蛇头从 {:?} 掉头到了 {:?}",
+                seed,
+                tick,
+                old_direction,
+                snake.get_head_direction()
+            );
+
+            let length_before = snake.snake_body_length;
+            step(&mut snake, &mut state);
+            check_invariants(&snake, &state, seed, tick);
+            assert!(snake.snake_body_length - length_before <= 1);
+
+            if state.get_game_is_suspend() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn movement_invariants_hold_for_random_games() {
+        for seed in 0..GAMES {
+            simulate(seed);
+        }
+    }
+
+    #[test]
+    fn same_seed_generates_same_food() {
+        let eat_and_report = || {
+            // 蛇头右边就是食物，吃掉后新食物的位置只取决于种子
+            let mut snake = Snake::from_body(
+                [Position { x: 5, y: 5 }, Position { x: 4, y: 5 }],
+                Direction::Right,
+                Position { x: 6, y: 5 },
+                40,
+                15,
+            )
+            .unwrap();
+            snake.set_food_seed(42);
+            let mut state = GameState::with_historic_score(GameMode::Endless, 0);
+            step(&mut snake, &mut state);
+            assert_eq!(state.get_game_score(), 1);
+            snake.get_snak_food_position()
+        };
+        assert_eq!(eat_and_report(), eat_and_report());
+    }
+
+    #[test]
+    fn food_can_spawn_in_first_row_and_column() {
+        // 2x2 棋盘上蛇占了三格，唯一的空位在第 0 行第 0 列
+        let mut snake = Snake::from_body(
+            [
+                Position { x: 1, y: 0 },
+                Position { x: 1, y: 1 },
+                Position { x: 0, y: 1 },
+            ],
+            Direction::Up,
+            Position { x: 0, y: 0 },
+            2,
+            2,
+        )
+        .unwrap();
+        assert!(snake.generate_food());
+        assert_eq!(snake.get_snak_food_position(), Position { x: 0, y: 0 });
+    }
+
+    #[test]
+    fn filling_the_board_wins() {
+        let mut snake = Snake::from_body(
+            [Position { x: 1, y: 0 }, Position { x: 0, y: 0 }],
+            Direction::Right,
+            Position { x: 2, y: 0 },
+            4,
+            1,
+        )
+        .unwrap();
+        let mut state = GameState::with_historic_score(GameMode::Endless, 0);
+
+        step(&mut snake, &mut state);
+        assert_eq!(snake.get_snak_food_position(), Position { x: 3, y: 0 });
+        assert_eq!(state.get_game_outcome(), None);
+
+        step(&mut snake, &mut state);
+        assert_eq!(state.get_game_outcome(), Some(GameOutcome::BoardFull));
+        assert_eq!(state.get_game_score(), 2);
+        assert_eq!(snake.get_snake_body().len(), 4);
+    }
+
+    #[test]
+    fn obstacles_block_the_snake_and_food() {
+        // 1x3 的棋盘：障碍物盖住了食物，食物换到唯一剩下的空位
+        let mut snake = Snake::from_body(
+            [Position { x: 0, y: 0 }],
+            Direction::Right,
+            Position { x: 2, y: 0 },
+            3,
+            1,
+        )
+        .unwrap();
+        snake.place_obstacles([Position { x: 2, y: 0 }]);
+        assert_eq!(snake.get_snak_food_position(), Position { x: 1, y: 0 });
+
+        // 1x4 的棋盘：蛇身和棋盘外的位置被跳过
+        let mut snake = Snake::from_body(
+            [Position { x: 0, y: 0 }],
+            Direction::Right,
+            Position { x: 1, y: 0 },
+            4,
+            1,
+        )
+        .unwrap();
+        snake.place_obstacles([
+            Position { x: 2, y: 0 },
+            Position { x: 0, y: 0 },
+            Position { x: 9, y: 9 },
+        ]);
+        assert_eq!(
+            snake.get_obstacles().collect::<Vec<_>>(),
+            [Position { x: 2, y: 0 }]
+        );
+        assert!(!snake.is_touch_self_body(&Position { x: 2, y: 0 }));
+
+        let mut state = GameState::with_historic_score(GameMode::Endless, 0);
+        step(&mut snake, &mut state);
+        assert_eq!(state.get_game_score(), 1);
+        assert_eq!(snake.get_snak_food_position(), Position { x: 3, y: 0 });
+        step(&mut snake, &mut state);
+        assert_eq!(state.get_game_outcome(), Some(GameOutcome::HitObstacle));
+    }
+
+    #[test]
+    fn invalid_boards_and_bodies_are_rejected() {
+        assert!(Snake::new(4, 4).is_err());
+        // 6x6 放得下蛇，但放不下 (5, 8) 的食物
+        assert!(Snake::new(6, 6).is_err());
+        let snake = Snake::new(6, 9).unwrap();
+        let food = snake.get_snak_food_position();
+        assert!(!snake.is_out_of_bounds(&food));
+        assert!(!snake.get_snake_body().contains(&food));
+
+        let food = Position { x: 0, y: 0 };
+        let build = |body: Vec<Position>| Snake::from_body(body, Direction::Right, food, 5, 5);
+        assert!(build(Vec::new()).is_err());
+        assert!(build(vec![Position { x: 5, y: 0 }]).is_err());
+        assert!(build(vec![Position { x: 0, y: -1 }]).is_err());
+        let overlapping = vec![Position { x: 1, y: 1 }, Position { x: 1, y: 1 }];
+        assert!(build(overlapping).is_err());
+        assert!(build(vec![Position { x: 4, y: 4 }]).is_ok());
+
+        let body = [Position { x: 1, y: 1 }];
+        let with_food = |food| Snake::from_body(body, Direction::Right, food, 5, 5);
+        assert!(with_food(Position { x: 1, y: 1 }).is_err());
+        assert!(with_food(Position { x: 5, y: 1 }).is_err());
+        assert!(with_food(Position { x: -1, y: 0 }).is_err());
+    }
 }
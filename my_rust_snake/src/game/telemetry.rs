@@ -0,0 +1,452 @@
+/*
+在这个文件里面实现逐 tick 的遥测日志以及日志分析
+TelemetryLogger：每局开始写一行 start，之后每个 tick 写一行 tick（JSON Lines），
+记录蛇头位置、方向、收到的输入、食物位置、分数以及这个 tick 实际耗时
+analyze：读取日志，统计玩家的反应时间，并把死亡位置画成棋盘热力图
+*/
+use crate::game::GameState;
+use crate::game::game_direction::Direction;
+use crate::game::game_error::GameError;
+use crate::game::game_mode::{GameMode, GameOutcome};
+use crate::game::snake::{Position, Snake};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// 热力图中死亡次数由少到多使用的字符
+const HEAT_LEVELS: [char; 5] = [' ', '.', ':', '*', '#'];
+
+/// 一个 tick 的遥测数据
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TickSample {
+    pub tick: u64,
+    pub head: Position,
+    pub direction: Direction,
+    /// 这个 tick 收到的输入，没有输入时为 None
+    pub input: Option<Direction>,
+    pub food: Position,
+    pub score: usize,
+    /// 从上一个 tick 到这个 tick 实际经过的时间
+    pub tick_duration: Duration,
+    pub outcome: Option<GameOutcome>,
+}
+
+impl TickSample {
+    /// 在 step 之后采集当前状态
+    pub fn capture(
+        snake: &Snake,
+        state: &GameState,
+        input: Option<Direction>,
+        tick_duration: Duration,
+    ) -> Self {
+        TickSample {
+            tick: state.get_game_tick(),
+            head: snake.get_head_position(),
+            direction: snake.get_head_direction(),
+            input,
+            food: snake.get_snak_food_position(),
+            score: state.get_game_score(),
+            tick_duration,
+            outcome: state.get_game_outcome(),
+        }
+    }
+
+    fn to_json(self, game: u64) -> String {
+        let input = self
+            .input
+            .map_or("null".to_string(), |d| format!("\"{}\"", d.name()));
+        let outcome = self
+            .outcome
+            .map_or("null".to_string(), |o| format!("\"{}\"", o.id()));
+        format!(
+            "{{\"type\":\"tick\",\"game\":{},\"tick\":{},\"head\":[{},{}],\"direction\":\"{}\",\
+             \"input\":{},\"food\":[{},{}],\"score\":{},\"tick_ms\":{:.3},\"outcome\":{}}}",
+            game,
+            self.tick,
+            self.head.x,
+            self.head.y,
+            self.direction.name(),
+            input,
+            self.food.x,
+            self.food.y,
+            self.score,
+            self.tick_duration.as_secs_f64() * 1000.0,
+            outcome
+        )
+    }
+}
+
+/// 遥测日志：每个 tick 一行 JSON
+pub struct TelemetryLogger<W: Write> {
+    out: W,
+    //本次运行中的第几局，从 1 开始
+    game: u64,
+}
+
+impl TelemetryLogger<BufWriter<File>> {
+    /// 以追加方式打开日志文件，多次运行的数据会写进同一个文件
+    pub fn open(path: &Path) -> Result<Self, GameError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(GameError::Log)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> TelemetryLogger<W> {
+    pub fn new(out: W) -> Self {
+        TelemetryLogger { out, game: 0 }
+    }
+
+    /// 新的一局开始，记录模式和棋盘大小
+    pub fn start_game(
+        &mut self,
+        mode: GameMode,
+        width: usize,
+        height: usize,
+    ) -> Result<(), GameError> {
+        self.game += 1;
+        writeln!(
+            self.out,
+            "{{\"type\":\"start\",\"game\":{},\"mode\":\"{}\",\"width\":{},\"height\":{}}}",
+            self.game,
+            mode.name(),
+            width,
+            height
+        )
+        .map_err(GameError::Log)
+    }
+
+    pub fn record(&mut self, sample: &TickSample) -> Result<(), GameError> {
+        writeln!(self.out, "{}", sample.to_json(self.game)).map_err(GameError::Log)?;
+        // 一局结束时立即落盘，防止强制退出时丢失
+        if sample.outcome.is_some() {
+            self.out.flush().map_err(GameError::Log)?;
+        }
+        Ok(())
+    }
+}
+
+/// 日志中的 JSON 值，只需要支持 TelemetryLogger 写出的几种类型
+#[derive(PartialEq, Debug)]
+enum JsonValue {
+    Null,
+    Number(f64),
+    Text(String),
+    Array(Vec<f64>),
+}
+
+/// 解析一行不含嵌套对象的 JSON
+fn parse_json_line(line: &str) -> Result<Vec<(String, JsonValue)>, String> {
+    let mut chars = line.trim().chars().peekable();
+    let mut fields = Vec::new();
+    let expect = |chars: &mut std::iter::Peekable<std::str::Chars>, c: char| {
+        skip_whitespace(chars);
+        (chars.next() == Some(c))
+            .then_some(())
+            .ok_or(format!("缺少 `{}`", c))
+    };
+    expect(&mut chars, '{')?;
+    skip_whitespace(&mut chars);
+    if chars.peek() == Some(&'}') {
+        return Ok(fields);
+    }
+    loop {
+        skip_whitespace(&mut chars);
+        let key = parse_json_string(&mut chars)?;
+        expect(&mut chars, ':')?;
+        skip_whitespace(&mut chars);
+        let value = match chars.peek() {
+            Some('"') => JsonValue::Text(parse_json_string(&mut chars)?),
+            Some('[') => {
+                chars.next();
+                let mut items = Vec::new();
+                loop {
+                    skip_whitespace(&mut chars);
+                    if chars.peek() == Some(&']') {
+                        chars.next();
+                        break;
+                    }
+                    items.push(parse_json_number(&mut chars)?);
+                    skip_whitespace(&mut chars);
+                    if chars.peek() == Some(&',') {
+                        chars.next();
+                    }
+                }
+                JsonValue::Array(items)
+            }
+            Some('n') => {
+                let word: String = chars.by_ref().take(4).collect();
+                if word != "null" {
+                    return Err(format!("无法识别的值：{}", word));
+                }
+                JsonValue::Null
+            }
+            _ => JsonValue::Number(parse_json_number(&mut chars)?),
+        };
+        fields.push((key, value));
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            Some(',') => continue,
+            Some('}') => return Ok(fields),
+            _ => return Err("缺少 `,` 或 `}`".to_string()),
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn parse_json_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String, String> {
+    if chars.next() != Some('"') {
+        return Err("缺少字符串".to_string());
+    }
+    let mut text = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(text),
+            Some('\\') => text.extend(chars.next()),
+            Some(c) => text.push(c),
+            None => return Err("字符串没有结束".to_string()),
+        }
+    }
+}
+
+fn parse_json_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<f64, String> {
+    let mut text = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || "+-.eE".contains(*c)) {
+        text.push(c);
+    }
+    text.parse::<f64>()
+        .map_err(|_| format!("无法解析的数字：{}", text))
+}
+
+/// 日志分析结果
+#[derive(Debug, Default)]
+pub struct TelemetryReport {
+    games: usize,
+    ticks: usize,
+    //每次反应耗费的毫秒数和 tick 数
+    reactions: Vec<(f64, u64)>,
+    //撞墙、撞到自己或障碍物时蛇头所在的格子
+    deaths: Vec<Position>,
+    width: usize,
+    height: usize,
+}
+
+impl TelemetryReport {
+    /// 生成文字报告：对局统计、反应时间以及死亡位置热力图
+    pub fn to_text(&self) -> String {
+        let mut report = format!("对局数：{}  tick 数：{}\n", self.games, self.ticks);
+
+        report.push_str("反应时间（食物出现到下一次操作，以 tick 为精度）：\n");
+        if self.reactions.is_empty() {
+            report.push_str("  没有记录到操作\n");
+        } else {
+            let mut millis: Vec<f64> = self.reactions.iter().map(|r| r.0).collect();
+            millis.sort_by(f64::total_cmp);
+            let count = millis.len();
+            let mean = millis.iter().sum::<f64>() / count as f64;
+            let median = if count % 2 == 1 {
+                millis[count / 2]
+            } else {
+                (millis[count / 2 - 1] + millis[count / 2]) / 2.0
+            };
+            let mean_ticks = self.reactions.iter().map(|r| r.1).sum::<u64>() as f64 / count as f64;
+            report.push_str(&format!(
+                "  次数 {}  平均 {:.0} ms（{:.1} tick）  中位数 {:.0} ms  最快 {:.0} ms  最慢 {:.0} ms\n",
+                count,
+                mean,
+                mean_ticks,
+                median,
+                millis[0],
+                millis[count - 1]
+            ));
+        }
+
+        report.push_str(&format!(
+            "死亡位置热力图（{} 次死亡，{} 表示次数由少到多）：\n",
+            self.deaths.len(),
+            HEAT_LEVELS[1..].iter().collect::<String>()
+        ));
+        let mut counts = vec![0usize; self.width * self.height];
+        for death in &self.deaths {
+            let (x, y) = (death.x as usize, death.y as usize);
+            if x < self.width && y < self.height {
+                counts[y * self.width + x] += 1;
+            }
+        }
+        let max = counts.iter().copied().max().unwrap_or(0);
+        let border = format!("+{}+\n", "-".repeat(self.width));
+        report.push_str(&border);
+        for row in counts.chunks(self.width.max(1)).take(self.height) {
+            report.push('|');
+            for &count in row {
+                // 按最大次数等比例分级，只要死过一次就至少显示最低一级
+                let level = (count * (HEAT_LEVELS.len() - 1)).div_ceil(max.max(1));
+                report.push(HEAT_LEVELS[level]);
+            }
+            report.push_str("|\n");
+        }
+        report.push_str(&border);
+        report
+    }
+}
+
+/// 分析遥测日志，格式错误时返回带行号的描述
+pub fn analyze(log: &str) -> Result<TelemetryReport, String> {
+    let mut report = TelemetryReport::default();
+    //本局已经经过的毫秒数，以及等待玩家反应的刺激（食物出现）发生的时间和 tick
+    let mut elapsed = 0.0;
+    let mut stimulus: Option<(f64, u64)> = None;
+    let mut previous_food = None;
+
+    for (index, line) in log.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let error = |msg: String| format!("第 {} 行：{}", index + 1, msg);
+        let fields = parse_json_line(line).map_err(error)?;
+        let field = |name: &str| fields.iter().find(|(key, _)| key == name).map(|f| &f.1);
+        let number = |name: &str| match field(name) {
+            Some(JsonValue::Number(n)) => Ok(*n),
+            _ => Err(error(format!("缺少数字字段 {}", name))),
+        };
+        let position = |name: &str| match field(name) {
+            Some(JsonValue::Array(xy)) if xy.len() == 2 => Ok(Position {
+                x: xy[0] as i32,
+                y: xy[1] as i32,
+            }),
+            _ => Err(error(format!("缺少坐标字段 {}", name))),
+        };
+
+        match field("type") {
+            Some(JsonValue::Text(kind)) if kind == "start" => {
+                report.games += 1;
+                report.width = report.width.max(number("width")? as usize);
+                report.height = report.height.max(number("height")? as usize);
+                // 开局时食物已经在棋盘上，从这一刻开始计算第一次反应
+                elapsed = 0.0;
+                stimulus = Some((0.0, 0));
+                previous_food = None;
+            }
+            Some(JsonValue::Text(kind)) if kind == "tick" => {
+                report.ticks += 1;
+                let tick = number("tick")? as u64;
+                elapsed += number("tick_ms")?;
+                if let Some(JsonValue::Text(_)) = field("input")
+                    && let Some((since, since_tick)) = stimulus.take()
+                {
+                    report
+                        .reactions
+                        .push((elapsed - since, tick.saturating_sub(since_tick)));
+                }
+                let food = position("food")?;
+                if previous_food.is_some_and(|previous| previous != food) {
+                    stimulus = Some((elapsed, tick));
+                }
+                previous_food = Some(food);
+                if let Some(JsonValue::Text(outcome)) = field("outcome")
+                    && GameOutcome::from_id(outcome).is_some_and(|o| !o.is_win())
+                {
+                    report.deaths.push(position("head")?);
+                }
+            }
+            _ => return Err(error("未知的记录类型".to_string())),
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(tick: u64, input: Option<Direction>, food: (i32, i32)) -> TickSample {
+        TickSample {
+            tick,
+            head: Position { x: 2, y: 1 },
+            direction: Direction::Right,
+            input,
+            food: Position {
+                x: food.0,
+                y: food.1,
+            },
+            score: 0,
+            tick_duration: Duration::from_millis(250),
+            outcome: None,
+        }
+    }
+
+    #[test]
+    fn logger_writes_start_and_tick_lines() {
+        let mut logger = TelemetryLogger::new(Vec::new());
+        logger.start_game(GameMode::Endless, 4, 3).unwrap();
+        logger
+            .record(&sample(1, Some(Direction::Up), (3, 0)))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(logger.out).unwrap(),
+            "{\"type\":\"start\",\"game\":1,\"mode\":\"endless\",\"width\":4,\"height\":3}\n\
+             {\"type\":\"tick\",\"game\":1,\"tick\":1,\"head\":[2,1],\"direction\":\"right\",\
+             \"input\":\"up\",\"food\":[3,0],\"score\":0,\"tick_ms\":250.000,\"outcome\":null}\n"
+        );
+    }
+
+    #[test]
+    fn analyzer_reports_reactions_and_death_heatmap() {
+        let mut logger = TelemetryLogger::new(Vec::new());
+        for _ in 0..2 {
+            logger.start_game(GameMode::Endless, 4, 3).unwrap();
+            // 开局第 2 个 tick 才操作；第 3 个 tick 吃到食物，之后第 5 个 tick 才操作
+            logger.record(&sample(1, None, (3, 0))).unwrap();
+            logger
+                .record(&sample(2, Some(Direction::Up), (3, 0)))
+                .unwrap();
+            logger.record(&sample(3, None, (0, 2))).unwrap();
+            logger.record(&sample(4, None, (0, 2))).unwrap();
+            logger
+                .record(&sample(5, Some(Direction::Left), (0, 2)))
+                .unwrap();
+            let mut last = sample(6, None, (0, 2));
+            last.outcome = Some(GameOutcome::HitWall);
+            logger.record(&last).unwrap();
+        }
+        let log = String::from_utf8(logger.out).unwrap();
+        let report = analyze(&log).unwrap();
+        assert_eq!(report.games, 2);
+        assert_eq!(report.ticks, 12);
+        assert_eq!(
+            report.reactions,
+            [(500.0, 2), (500.0, 2), (500.0, 2), (500.0, 2)]
+        );
+        assert_eq!(report.deaths.len(), 2);
+        assert_eq!(
+            report.to_text(),
+            "对局数：2  tick 数：12\n\
+             反应时间（食物出现到下一次操作，以 tick 为精度）：\n\
+             \x20 次数 4  平均 500 ms（2.0 tick）  中位数 500 ms  最快 500 ms  最慢 500 ms\n\
+             死亡位置热力图（2 次死亡，.:*# 表示次数由少到多）：\n\
+             +----+\n\
+             |    |\n\
+             |  # |\n\
+             |    |\n\
+             +----+\n"
+        );
+    }
+
+    #[test]
+    fn analyzer_rejects_bad_lines() {
+        assert!(
+            analyze("{\"type\":\"start\"}")
+                .unwrap_err()
+                .starts_with("第 1 行")
+        );
+        assert!(analyze("\nnot json").unwrap_err().starts_with("第 2 行"));
+        assert!(analyze("{\"type\":\"other\"}").is_err());
+    }
+}
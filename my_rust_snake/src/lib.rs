@@ -0,0 +1,57 @@
+//! 终端贪吃蛇游戏引擎
+//!
+//! 游戏规则、状态与渲染都在 [`game`] 模块中，常用类型在 crate 根部重新导出，
+//! 机器人、对战平台或服务端可以直接依赖本 crate 驱动游戏，而不必经过终端界面。
+//!
+//! 一局游戏由 [`Snake`]、[`GameState`] 和 [`Canvas`] 组成：
+//! 每个 tick 从某个 [`InputDevice`] 取得指令，用 [`steer`] 更新方向，
+//! 调用 [`step`] 推进一帧，再用 [`Canvas::render_canvas`] 交给任意 [`Renderer`] 输出。
+//!
+//! ```
+//! use my_rust_snake::{
+//!     Canvas, Direction, GameMode, GameState, InputDevice, MemoryRenderer, ScriptInput, Snake,
+//!     steer, step,
+//! };
+//!
+//! let mut canvas = Canvas::new();
+//! let mut snake = Snake::new(canvas.get_canvas_width(), canvas.get_canvas_height()).unwrap();
+//! snake.set_food_seed(7);
+//! // 不读取存档文件，历史最高分从 0 开始
+//! let mut state = GameState::with_historic_score(GameMode::Endless, 0);
+//! let mut input = ScriptInput::parse("D3 L").unwrap();
+//! let mut renderer = MemoryRenderer::new();
+//!
+//! while !state.get_game_is_suspend() {
+//!     match input.direction_check() {
+//!         Some(Direction::Quit) => break,
+//!         Some(dir) => {
+//!             steer(&mut snake, &mut state, dir);
+//!         }
+//!         None => {}
+//!     }
+//!     step(&mut snake, &mut state);
+//!     canvas.render_canvas(&mut renderer, &snake, &state).unwrap();
+//! }
+//!
+//! // 初始蛇头在 (5,5)，向下走三格、向左一格
+//! assert_eq!(snake.get_head_position(), my_rust_snake::Position { x: 4, y: 8 });
+//! assert_eq!(state.get_game_tick(), 4);
+//! assert!(renderer.snapshot().contains("当前分数"));
+//! ```
+
+pub mod game;
+
+pub use game::achievement::{Achievement, Achievements};
+pub use game::daily::{ChallengeDate, DailyResult};
+pub use game::game_direction::{Direction, InputDevice, KeyboardInput, MouseInput, ScriptInput};
+pub use game::game_display::{BoardLayout, BorderFlash, Canvas};
+pub use game::game_error::GameError;
+pub use game::game_event::{
+    EventBus, EventLogger, EventSubscriber, FeedbackStyle, GameEvent, TerminalFeedback,
+};
+pub use game::game_mode::{GameMode, GameOutcome};
+pub use game::game_renderer::{AnsiRenderer, CrosstermRenderer, MemoryRenderer, Renderer};
+pub use game::replay::{Recording, Replay};
+pub use game::snake::{Position, Snake};
+pub use game::telemetry::{TelemetryLogger, TelemetryReport, TickSample};
+pub use game::{GameState, TICK_MILLIS, spawn_snake, steer, step};
@@ -1,20 +1,34 @@
 // #![allow(dead_code)]
 // #![allow(unused)]
 
+// 终端前端：只负责解析命令行、接管终端和驱动游戏循环，游戏引擎本身在 lib.rs 中
 mod app;
-mod game;
+mod cli;
 use crossterm::{
-    cursor, execute,
+    cursor,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use std::io::{self, stdout};
+use std::panic;
 
 /// 终端守卫者：负责自动进入和退出特殊模式
 pub struct TerminalGuard;
 impl TerminalGuard {
-    pub fn new() -> io::Result<Self> {
+    pub fn new(capture_mouse: bool) -> io::Result<Self> {
+        // 先安装 panic 钩子：panic 时先恢复终端，再打印 panic 信息，避免信息被交替屏幕吞掉
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            default_hook(info);
+        }));
+
         enable_raw_mode()?; // 开启原始模式
         execute!(stdout(), EnterAlternateScreen, cursor::Hide)?; // 进入交替屏幕，隐藏光标
+        if capture_mouse {
+            execute!(stdout(), EnableMouseCapture)?; // 让终端上报鼠标事件
+        }
         Ok(TerminalGuard)
     }
 }
@@ -22,12 +36,52 @@ impl TerminalGuard {
 // 当 TerminalGuard 变量生命周期结束时，自动执行以下代码
 impl Drop for TerminalGuard {
     fn drop(&mut self) {
-        let _ = execute!(stdout(), cursor::Show, LeaveAlternateScreen); // 显示光标，退出交替屏幕
-        let _ = disable_raw_mode(); // 关闭原始模式
+        restore_terminal();
     }
 }
+
+/// 恢复终端：可以安全地重复调用
+fn restore_terminal() {
+    let _ = execute!(stdout(), DisableMouseCapture); // 关闭鼠标事件上报（未开启时无副作用）
+    let _ = execute!(stdout(), cursor::Show, LeaveAlternateScreen); // 显示光标，退出交替屏幕
+    let _ = disable_raw_mode(); // 关闭原始模式
+}
 fn main() -> io::Result<()> {
-    let _guard = TerminalGuard::new()?;
-    app::run();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match cli::parse(&args) {
+        Ok(cli::Command::Play(options)) => options,
+        Ok(cli::Command::Achievements) => {
+            app::show_achievements()?;
+            return Ok(());
+        }
+        Ok(cli::Command::DailyExport { date, out }) => {
+            app::export_daily(date, out.as_deref())?;
+            return Ok(());
+        }
+        Ok(cli::Command::Export {
+            recording,
+            out,
+            format,
+        }) => {
+            app::export_replay(&recording, &out, format)?;
+            return Ok(());
+        }
+        Ok(cli::Command::Analyze(log)) => {
+            app::analyze_telemetry(&log)?;
+            return Ok(());
+        }
+        Err(e) => {
+            eprintln!("{}\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // 无界面模式不需要接管终端
+    let _guard = if options.headless {
+        None
+    } else {
+        Some(TerminalGuard::new(options.input == cli::InputKind::Mouse)?)
+    };
+    app::run(&options)?;
     Ok(())
 }